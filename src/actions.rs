@@ -0,0 +1,383 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+
+/// Turns keys, mouse buttons and gamepads into [`Action`]s so that gameplay doesn't care how it
+/// is controlled.
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bindings>()
+            .init_resource::<StickSettings>()
+            .init_resource::<ActionState>()
+            .add_systems(PreUpdate, update_actions.after(InputSystem))
+            .add_systems(Update, log_gamepads);
+    }
+}
+
+/// Something the player can do, whichever key they do it with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Pause,
+    /// Restarts the level.
+    Reset,
+    ZoomIn,
+    ZoomOut,
+    Dash,
+    GroundPound,
+    /// Stops the ball in levels with the brake ability.
+    Brake,
+    /// Saves a replay of the attempt so far.
+    SaveReplay,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Pause,
+        Action::Reset,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::Dash,
+        Action::GroundPound,
+        Action::Brake,
+        Action::SaveReplay,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "Forward",
+            Action::MoveBack => "Back",
+            Action::MoveLeft => "Left",
+            Action::MoveRight => "Right",
+            Action::Jump => "Jump",
+            Action::Pause => "Pause",
+            Action::Reset => "Reset",
+            Action::ZoomIn => "Zoom In",
+            Action::ZoomOut => "Zoom Out",
+            Action::Dash => "Dash",
+            Action::GroundPound => "Ground Pound",
+            Action::Brake => "Brake",
+            Action::SaveReplay => "Save Replay",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// A button on any connected gamepad.
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Mouse(button) => format!("Mouse {:?}", button),
+            Binding::Gamepad(button) => format!("Pad {:?}", button),
+        }
+    }
+}
+
+/// Which keys and buttons trigger each action. Actions missing from a saved table get their
+/// default bindings.
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    pub move_forward: Vec<Binding>,
+    pub move_back: Vec<Binding>,
+    pub move_left: Vec<Binding>,
+    pub move_right: Vec<Binding>,
+    pub jump: Vec<Binding>,
+    pub pause: Vec<Binding>,
+    pub reset: Vec<Binding>,
+    pub zoom_in: Vec<Binding>,
+    pub zoom_out: Vec<Binding>,
+    pub dash: Vec<Binding>,
+    pub ground_pound: Vec<Binding>,
+    pub brake: Vec<Binding>,
+    pub save_replay: Vec<Binding>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Binding::{Gamepad, Key};
+
+        // the left stick always moves the ball, so the d-pad can do it as well
+        Bindings {
+            move_forward: vec![
+                Key(KeyCode::W),
+                Key(KeyCode::Up),
+                Gamepad(GamepadButtonType::DPadUp),
+            ],
+            move_back: vec![
+                Key(KeyCode::S),
+                Key(KeyCode::Down),
+                Gamepad(GamepadButtonType::DPadDown),
+            ],
+            move_left: vec![
+                Key(KeyCode::A),
+                Key(KeyCode::Left),
+                Gamepad(GamepadButtonType::DPadLeft),
+            ],
+            move_right: vec![
+                Key(KeyCode::D),
+                Key(KeyCode::Right),
+                Gamepad(GamepadButtonType::DPadRight),
+            ],
+            jump: vec![Key(KeyCode::Space), Gamepad(GamepadButtonType::South)],
+            pause: vec![Key(KeyCode::Escape), Gamepad(GamepadButtonType::Start)],
+            reset: vec![Key(KeyCode::R), Gamepad(GamepadButtonType::Select)],
+            zoom_in: vec![
+                Key(KeyCode::Equals),
+                Gamepad(GamepadButtonType::RightTrigger),
+            ],
+            zoom_out: vec![Key(KeyCode::Minus), Gamepad(GamepadButtonType::LeftTrigger)],
+            dash: vec![Key(KeyCode::ShiftLeft), Gamepad(GamepadButtonType::West)],
+            ground_pound: vec![Key(KeyCode::Q), Gamepad(GamepadButtonType::East)],
+            brake: vec![Key(KeyCode::E), Gamepad(GamepadButtonType::North)],
+            save_replay: vec![Key(KeyCode::F9)],
+        }
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> &Vec<Binding> {
+        match action {
+            Action::MoveForward => &self.move_forward,
+            Action::MoveBack => &self.move_back,
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            Action::Jump => &self.jump,
+            Action::Pause => &self.pause,
+            Action::Reset => &self.reset,
+            Action::ZoomIn => &self.zoom_in,
+            Action::ZoomOut => &self.zoom_out,
+            Action::Dash => &self.dash,
+            Action::GroundPound => &self.ground_pound,
+            Action::Brake => &self.brake,
+            Action::SaveReplay => &self.save_replay,
+        }
+    }
+
+    pub fn get_mut(&mut self, action: Action) -> &mut Vec<Binding> {
+        match action {
+            Action::MoveForward => &mut self.move_forward,
+            Action::MoveBack => &mut self.move_back,
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::Jump => &mut self.jump,
+            Action::Pause => &mut self.pause,
+            Action::Reset => &mut self.reset,
+            Action::ZoomIn => &mut self.zoom_in,
+            Action::ZoomOut => &mut self.zoom_out,
+            Action::Dash => &mut self.dash,
+            Action::GroundPound => &mut self.ground_pound,
+            Action::Brake => &mut self.brake,
+            Action::SaveReplay => &mut self.save_replay,
+        }
+    }
+
+    /// Every action a key or button triggers.
+    pub fn actions_for(&self, binding: Binding) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|&action| self.get(action).contains(&binding))
+            .collect()
+    }
+
+    /// Whether a binding triggers more than one action.
+    pub fn is_conflicting(&self, binding: Binding) -> bool {
+        self.actions_for(binding).len() > 1
+    }
+
+    /// Each binding that triggers more than one action, with the actions it triggers.
+    pub fn conflicts(&self) -> Vec<(Binding, Vec<Action>)> {
+        let mut conflicts: Vec<(Binding, Vec<Action>)> = vec![];
+
+        for action in Action::ALL {
+            for &binding in self.get(action) {
+                if self.is_conflicting(binding)
+                    && !conflicts.iter().any(|(conflict, _)| *conflict == binding)
+                {
+                    conflicts.push((binding, self.actions_for(binding)));
+                }
+            }
+        }
+
+        conflicts
+    }
+}
+
+/// How far a stick has to move before it counts, as a fraction of its range.
+#[derive(Debug, Copy, Clone, Resource)]
+pub struct StickSettings {
+    pub deadzone: f32,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        StickSettings { deadzone: 0.15 }
+    }
+}
+
+/// Which actions are held this frame.
+#[derive(Default, Debug, Clone, Resource)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    any_just_pressed: bool,
+    /// The left stick of every gamepad combined.
+    pub move_stick: Vec2,
+    /// The right stick of every gamepad combined.
+    pub camera_stick: Vec2,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Whether any key or button was pressed this frame, even one that isn't bound to anything.
+    pub fn any_just_pressed(&self) -> bool {
+        self.any_just_pressed
+    }
+
+    /// -1, 0 or 1 depending on which of two opposing actions is held.
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        self.pressed(positive) as i32 as f32 - self.pressed(negative) as i32 as f32
+    }
+
+    /// Movement relative to the camera, with x to the right and y forward. Only sticks give a
+    /// length between 0 and 1.
+    pub fn movement(&self) -> Vec2 {
+        let buttons = Vec2::new(
+            self.axis(Action::MoveLeft, Action::MoveRight),
+            self.axis(Action::MoveBack, Action::MoveForward),
+        );
+
+        (buttons + self.move_stick).clamp_length_max(1.0)
+    }
+}
+
+/// Applies a round deadzone to a stick, rescaling what is left so it still starts at 0.
+fn apply_deadzone(stick: Vec2, deadzone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= deadzone {
+        return Vec2::ZERO;
+    }
+
+    stick / length * ((length - deadzone) / (1.0 - deadzone)).min(1.0)
+}
+
+fn read_stick(
+    gamepads: &Gamepads,
+    axes: &Axis<GamepadAxis>,
+    x: GamepadAxisType,
+    y: GamepadAxisType,
+    deadzone: f32,
+) -> Vec2 {
+    let stick: Vec2 = gamepads
+        .iter()
+        .map(|gamepad| {
+            let axis = |axis_type| {
+                axes.get(GamepadAxis::new(gamepad, axis_type))
+                    .unwrap_or(0.0)
+            };
+            apply_deadzone(Vec2::new(axis(x), axis(y)), deadzone)
+        })
+        .sum();
+
+    stick.clamp_length_max(1.0)
+}
+
+fn update_actions(
+    bindings: Res<Bindings>,
+    stick_settings: Res<StickSettings>,
+    key: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut state: ResMut<ActionState>,
+) {
+    let pressed = |binding: &Binding| match *binding {
+        Binding::Key(code) => key.pressed(code),
+        Binding::Mouse(button) => mouse.pressed(button),
+        Binding::Gamepad(button) => gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.pressed(GamepadButton::new(gamepad, button))),
+    };
+    let just_pressed = |binding: &Binding| match *binding {
+        Binding::Key(code) => key.just_pressed(code),
+        Binding::Mouse(button) => mouse.just_pressed(button),
+        Binding::Gamepad(button) => gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button))),
+    };
+
+    state.pressed.clear();
+    state.just_pressed.clear();
+    for action in Action::ALL {
+        let bound = bindings.get(action);
+        if bound.iter().any(&pressed) {
+            state.pressed.insert(action);
+        }
+        if bound.iter().any(&just_pressed) {
+            state.just_pressed.insert(action);
+        }
+    }
+    state.any_just_pressed = key.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+        || gamepad_buttons.get_just_pressed().next().is_some();
+
+    state.move_stick = read_stick(
+        &gamepads,
+        &gamepad_axes,
+        GamepadAxisType::LeftStickX,
+        GamepadAxisType::LeftStickY,
+        stick_settings.deadzone,
+    );
+    state.camera_stick = read_stick(
+        &gamepads,
+        &gamepad_axes,
+        GamepadAxisType::RightStickX,
+        GamepadAxisType::RightStickY,
+        stick_settings.deadzone,
+    );
+}
+
+/// Gamepads can be plugged in and out at any time, this just says so.
+fn log_gamepads(gamepads: Res<Gamepads>, mut connections: EventReader<GamepadConnectionEvent>) {
+    for event in connections.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                info!("Gamepad {} connected: {}.", event.gamepad.id, info.name)
+            }
+            GamepadConnection::Disconnected => {
+                info!("Gamepad {} disconnected.", event.gamepad.id)
+            }
+        }
+    }
+
+    if gamepads.is_changed() {
+        info!("{} gamepad(s) connected.", gamepads.iter().count());
+    }
+}
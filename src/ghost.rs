@@ -0,0 +1,260 @@
+use crate::level::logic::goal::LevelCompletedEvent;
+use crate::level::{LevelLoadedEvent, LevelObject, LevelState};
+use crate::player::ball::BallType;
+use crate::player::config::PlayerConfig;
+use crate::player::Player;
+use crate::replay::ReplayState;
+use crate::save::Profile;
+use crate::timer::{start_timer, tick_timer, SpeedrunTimer, TimerState};
+use crate::util::{data_dir, fnv1a};
+use crate::AppState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostSettings>()
+            .init_resource::<GhostRecorder>()
+            .add_systems(Update, spawn_ghost)
+            // the timer finishes in the fixed update before this
+            .add_systems(Update, save_best_ghost)
+            .add_systems(Update, (move_ghost, show_ghost))
+            .add_systems(
+                FixedUpdate,
+                record_ghost
+                    .after(start_timer)
+                    .before(tick_timer)
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// Number of physics ticks between samples in a ghost track.
+const SAMPLE_INTERVAL: u64 = 4;
+
+/// Whether the ghost is shown at all is [`Settings::ghost`](crate::save::Settings::ghost).
+#[derive(Debug, Clone, Resource)]
+pub struct GhostSettings {
+    /// A ghost track to race against instead of the personal best, from `--ghost <file>`.
+    pub chosen: Option<PathBuf>,
+}
+
+impl Default for GhostSettings {
+    fn default() -> Self {
+        let mut args = std::env::args().skip_while(|arg| arg != "--ghost").skip(1);
+
+        GhostSettings {
+            chosen: args.next().map(PathBuf::from),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct GhostSample {
+    pub pos: [f32; 3],
+    pub rot: [f32; 4],
+}
+
+/// The player's position and rotation every [`SAMPLE_INTERVAL`] ticks since the timer started.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct GhostTrack {
+    /// The ball the run started as, which the ghost is styled after.
+    #[serde(default)]
+    pub ball: BallType,
+    /// The ball's radius when the run was recorded. Tracks from before this was saved are sized
+    /// from the current config instead.
+    #[serde(default)]
+    pub radius: Option<f32>,
+    pub samples: Vec<GhostSample>,
+}
+
+impl GhostTrack {
+    /// Where a profile's ghosts are stored.
+    pub fn profile_dir(profile: &str) -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join("ghosts").join(profile))
+    }
+
+    /// Where the ghost for a level's personal best is stored.
+    pub fn best_path(profile: &str, level: &str) -> Option<PathBuf> {
+        Self::profile_dir(profile)
+            .map(|dir| dir.join(format!("{:016x}.ron", fnv1a(level.as_bytes()))))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<GhostTrack> {
+        Ok(ron::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, ron::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    /// The ghost's transform at a (possibly fractional) tick, or `None` once the track is over.
+    pub fn sample(&self, tick: f32) -> Option<Transform> {
+        let index = tick / SAMPLE_INTERVAL as f32;
+        let first = index.floor() as usize;
+        let a = self.samples.get(first)?;
+        let b = self.samples.get(first + 1).unwrap_or(a);
+        let t = index.fract();
+
+        Some(
+            Transform::from_translation(Vec3::from(a.pos).lerp(Vec3::from(b.pos), t))
+                .with_rotation(Quat::from_array(a.rot).slerp(Quat::from_array(b.rot), t)),
+        )
+    }
+}
+
+/// Collects the track of the current attempt so it can become the next ghost.
+#[derive(Default, Debug, Clone, Resource)]
+pub struct GhostRecorder {
+    pub track: GhostTrack,
+}
+
+/// The translucent ball replaying a previous run.
+#[derive(Debug, Clone, Component)]
+pub struct Ghost {
+    pub track: GhostTrack,
+}
+
+fn spawn_ghost(
+    mut commands: Commands,
+    settings: Res<GhostSettings>,
+    profile: Res<Profile>,
+    level_state: Res<LevelState>,
+    mut recorder: ResMut<GhostRecorder>,
+    mut level_load: EventReader<LevelLoadedEvent>,
+    config: Res<PlayerConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if level_load.read().next().is_some() {
+        recorder.track = GhostTrack::default();
+
+        let path = settings.chosen.clone().or_else(|| {
+            level_state
+                .handle
+                .as_ref()
+                .and_then(|handle| handle.path())
+                .and_then(|path| GhostTrack::best_path(&profile.id, &path.to_string()))
+        });
+
+        if let Some(track) = path.and_then(|path| GhostTrack::load(path).ok()) {
+            let properties = track.ball.properties();
+            let radius = track.radius.unwrap_or_else(|| properties.radius(&config));
+
+            commands
+                .spawn(Ghost { track })
+                .insert(LevelObject)
+                .insert(PbrBundle {
+                    mesh: meshes.add(
+                        shape::UVSphere {
+                            radius,
+                            sectors: 32,
+                            stacks: 32,
+                        }
+                        .into(),
+                    ),
+                    material: materials.add(StandardMaterial {
+                        base_color: properties.light_color.with_a(0.3),
+                        alpha_mode: AlphaMode::Blend,
+                        unlit: true,
+                        ..default()
+                    }),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                });
+        }
+    }
+
+    level_load.clear();
+}
+
+fn record_ghost(
+    timer: Res<SpeedrunTimer>,
+    mut recorder: ResMut<GhostRecorder>,
+    config: Res<PlayerConfig>,
+    player: Query<(&Transform, &BallType), With<Player>>,
+) {
+    if timer.state == TimerState::Running && timer.ticks % SAMPLE_INTERVAL == 0 {
+        if let Some((transform, &ball)) = player.iter().next() {
+            if recorder.track.samples.is_empty() {
+                recorder.track.ball = ball;
+                recorder.track.radius = Some(ball.properties().radius(&config));
+            }
+
+            recorder.track.samples.push(GhostSample {
+                pos: transform.translation.into(),
+                rot: transform.rotation.to_array(),
+            });
+        }
+    }
+}
+
+fn save_best_ghost(
+    profile: Res<Profile>,
+    timer: Res<SpeedrunTimer>,
+    recorder: Res<GhostRecorder>,
+    replay: Res<ReplayState>,
+    mut completed_events: EventReader<LevelCompletedEvent>,
+) {
+    if completed_events.read().next().is_some() {
+        if timer.new_best && replay.is_recording() {
+            if let Some(path) = timer
+                .level
+                .as_ref()
+                .and_then(|level| GhostTrack::best_path(&profile.id, level))
+            {
+                if let Err(err) = recorder.track.save(path) {
+                    error!("Error saving ghost: {}", err);
+                }
+            }
+        }
+    }
+
+    completed_events.clear();
+}
+
+fn move_ghost(
+    timer: Res<SpeedrunTimer>,
+    fixed_time: Res<Time<Fixed>>,
+    mut ghosts: Query<(&Ghost, &mut Transform)>,
+) {
+    let tick = match timer.state {
+        TimerState::Running => timer.ticks as f32 + fixed_time.overstep_percentage(),
+        _ => timer.ticks as f32,
+    };
+
+    for (ghost, mut transform) in ghosts.iter_mut() {
+        if let Some(sample) = ghost.track.sample(tick) {
+            *transform = sample;
+        }
+    }
+}
+
+fn show_ghost(
+    profile: Res<Profile>,
+    timer: Res<SpeedrunTimer>,
+    mut ghosts: Query<&mut Visibility, With<Ghost>>,
+) {
+    // the ghost only appears once the race has started
+    let visible = profile.settings.ghost && timer.state != TimerState::Waiting;
+    let target = if visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    for mut visibility in ghosts.iter_mut() {
+        if *visibility != target {
+            *visibility = target;
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::level::logic::key::Inventory;
+use crate::lives::Lives;
+use crate::player::{Health, Player};
+use crate::timer::{format_delta, format_ticks, SpeedrunTimer};
+use crate::AppState;
+use bevy::prelude::*;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_hud).add_systems(
+            Update,
+            (
+                show_hud,
+                update_key_display,
+                update_health_display,
+                update_lives_display,
+                update_timer_display,
+            ),
+        );
+    }
+}
+
+/// The root node of the in-game overlay.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct Hud;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct KeyDisplay;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct HealthDisplay;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct LivesDisplay;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct TimerDisplay;
+
+pub fn hud_text_style(assets: &AssetServer, color: Color) -> TextStyle {
+    TextStyle {
+        font: assets.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 24.0,
+        color,
+    }
+}
+
+fn setup_hud(mut commands: Commands, assets: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(Hud)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_sections([
+                    TextSection::new("", hud_text_style(&assets, Color::WHITE)),
+                    TextSection::new("", hud_text_style(&assets, Color::WHITE)),
+                ]))
+                .insert(TimerDisplay);
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    hud_text_style(&assets, Color::rgb(1.0, 0.4, 0.4)),
+                ))
+                .insert(HealthDisplay);
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    hud_text_style(&assets, Color::rgb(0.4, 1.0, 0.4)),
+                ))
+                .insert(LivesDisplay);
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    hud_text_style(&assets, Color::WHITE),
+                ))
+                .insert(KeyDisplay);
+        });
+}
+
+fn show_hud(app_state: Res<State<AppState>>, mut hud: Query<&mut Visibility, With<Hud>>) {
+    if app_state.is_changed() {
+        let visible = matches!(app_state.get(), AppState::InGame | AppState::PauseMenu);
+
+        for mut visibility in hud.iter_mut() {
+            *visibility = if visible {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+fn update_key_display(
+    inventory: Res<Inventory>,
+    mut display: Query<&mut Text, With<KeyDisplay>>,
+    assets: Res<AssetServer>,
+) {
+    if inventory.is_changed() {
+        for mut text in display.iter_mut() {
+            text.sections.clear();
+
+            if !inventory.keys.is_empty() {
+                text.sections.push(TextSection::new(
+                    "Keys:",
+                    hud_text_style(&assets, Color::WHITE),
+                ));
+                for key in inventory.keys.iter() {
+                    text.sections.push(TextSection::new(
+                        format!(" {}", key.id),
+                        hud_text_style(&assets, key.color),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn update_health_display(
+    health: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut display: Query<&mut Text, With<HealthDisplay>>,
+) {
+    if let Some(health) = health.iter().next() {
+        for mut text in display.iter_mut() {
+            text.sections[0].value = format!(
+                "Health: {:.0}/{:.0}",
+                health.current.max(0.0).ceil(),
+                health.max
+            );
+        }
+    }
+}
+
+fn update_lives_display(lives: Res<Lives>, mut display: Query<&mut Text, With<LivesDisplay>>) {
+    if lives.is_changed() {
+        for mut text in display.iter_mut() {
+            text.sections[0].value = match lives.remaining {
+                Some(remaining) => format!("Lives: {}", remaining),
+                None => String::new(),
+            };
+        }
+    }
+}
+
+fn update_timer_display(
+    timer: Res<SpeedrunTimer>,
+    mut display: Query<&mut Text, With<TimerDisplay>>,
+) {
+    if timer.is_changed() {
+        for mut text in display.iter_mut() {
+            text.sections[0].value = format_ticks(timer.ticks);
+
+            match timer.delta {
+                Some(delta) => {
+                    text.sections[1].value = format!(" {}", format_delta(delta));
+                    text.sections[1].style.color = if delta <= 0 {
+                        Color::rgb(0.4, 1.0, 0.4)
+                    } else {
+                        Color::rgb(1.0, 0.4, 0.4)
+                    };
+                }
+                None => text.sections[1].value.clear(),
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::actions::ActionState;
+use crate::level::serial::SerialLevel;
+use crate::level::{LevelLoadedEvent, LevelState};
+use crate::player::camera::PlayerCamera;
+use crate::replay::ReplayState;
+use crate::AppState;
+use bevy::prelude::*;
+
+/// Flies the camera through a level before the player gets control, if the level has a flyover.
+pub struct IntroPlugin;
+
+impl Plugin for IntroPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Intro>()
+            .add_systems(Update, start_intro)
+            .add_systems(Update, play_intro.run_if(in_state(AppState::Intro)));
+    }
+}
+
+/// How long the camera takes to get from the end of the flyover to behind the ball, in seconds.
+const BLEND_OUT_TIME: f32 = 0.6;
+
+#[derive(Debug, Copy, Clone)]
+pub struct FlyoverPoint {
+    pub position: Vec3,
+    /// Where the camera looks from this point.
+    pub look_at: Vec3,
+}
+
+/// A path for the camera to fly along, from the level file.
+#[derive(Debug, Clone, Component)]
+pub struct Flyover {
+    /// Passed through smoothly in order. Never empty.
+    pub points: Vec<FlyoverPoint>,
+    /// In seconds
+    pub duration: f32,
+}
+
+impl Flyover {
+    /// Where the camera is at some point through the flyover, from 0 at the start to 1 at the end.
+    pub fn sample(&self, progress: f32) -> Transform {
+        // ease in and out so the camera doesn't start or stop with a jolt
+        let progress = progress.clamp(0.0, 1.0);
+        let progress = progress * progress * (3.0 - 2.0 * progress);
+
+        let last = self.points.len() - 1;
+        let along = progress * last as f32;
+        let index = (along.floor() as usize).min(last.saturating_sub(1));
+        let t = along - index as f32;
+
+        let point =
+            |offset: isize| self.points[(index as isize + offset).clamp(0, last as isize) as usize];
+        let (p0, p1, p2, p3) = (point(-1), point(0), point(1), point(2));
+
+        let position = catmull_rom(p0.position, p1.position, p2.position, p3.position, t);
+        let look_at = catmull_rom(p0.look_at, p1.look_at, p2.look_at, p3.look_at, t);
+
+        Transform::from_translation(position).looking_at(look_at, Vec3::Y)
+    }
+}
+
+/// A curve through `p1` and `p2`, shaped by the points either side of them.
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t)
+}
+
+/// The level intro being played.
+#[derive(Default, Debug, Clone, Resource)]
+pub struct Intro {
+    pub flyover: Option<Flyover>,
+    /// Seconds since the flyover started.
+    pub time: f32,
+    /// The level the intro was last played for, so restarting it doesn't play the intro again.
+    played: Option<AssetId<SerialLevel>>,
+}
+
+impl Intro {
+    pub fn is_playing(&self) -> bool {
+        self.flyover.is_some()
+    }
+}
+
+/// Starts the flyover of a level that has just loaded. `set_in_game` runs after this to decide
+/// whether to play it or go straight into the game.
+pub fn start_intro(
+    mut intro: ResMut<Intro>,
+    app_state: Res<State<AppState>>,
+    level_state: Res<LevelState>,
+    replay: Res<ReplayState>,
+    flyovers: Query<&Flyover>,
+    mut level_load: EventReader<LevelLoadedEvent>,
+) {
+    // coming back to the level from the menu plays the intro again
+    if level_state.handle.is_none() {
+        intro.played = None;
+    }
+
+    if let Some(level) = level_load.read().next() {
+        let id = level_state.handle.as_ref().map(Handle::id);
+        let playing_back = matches!(*replay, ReplayState::Playing { .. });
+
+        if *app_state.get() == AppState::Loading && intro.played != id && !playing_back {
+            if let Some(flyover) = level
+                .entities
+                .intro
+                .and_then(|entity| flyovers.get(entity).ok())
+            {
+                intro.flyover = Some(flyover.clone());
+                intro.time = 0.0;
+                intro.played = id;
+
+                info!("Playing level intro.");
+            }
+        }
+    }
+
+    level_load.clear();
+}
+
+fn play_intro(
+    mut intro: ResMut<Intro>,
+    mut camera: Query<(&mut Transform, &mut PlayerCamera)>,
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let (mut transform, mut player_camera) = camera.single_mut();
+    intro.time += time.delta_seconds();
+
+    if let Some(flyover) = &intro.flyover {
+        let progress = intro.time / flyover.duration;
+        *transform = flyover.sample(progress);
+
+        if progress < 1.0 {
+            if !actions.any_just_pressed() {
+                return;
+            }
+
+            info!("Level intro skipped.");
+        }
+    }
+
+    intro.flyover = None;
+
+    // ease from wherever the flyover stopped to behind the ball
+    player_camera.blend_from = *transform;
+    player_camera.blend = 0.0;
+    player_camera.blend_time = BLEND_OUT_TIME;
+
+    next_state.set(AppState::InGame);
+}
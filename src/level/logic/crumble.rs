@@ -0,0 +1,167 @@
+//! Platforms that break apart after the player touches them.
+
+use crate::player::{player_exists, Player};
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (detect_crumble_contacts, update_crumbling)
+            .chain()
+            .after(PhysicsSet::Writeback)
+            .run_if(player_exists)
+            .run_if(in_state(AppState::InGame)),
+    );
+}
+
+/// How far a shaking object moves away from its original position.
+const SHAKE_AMOUNT: f32 = 0.04;
+
+/// What happens to a crumbling object once it has finished shaking.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum CrumbleMode {
+    /// The object becomes dynamic and falls.
+    #[default]
+    Fall,
+    /// The object disappears.
+    Vanish,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CrumbleState {
+    Intact { contact_time: f32 },
+    Shaking { time: f32 },
+    Gone { time: f32 },
+}
+
+/// An object that crumbles after the player touches it for long enough or hits it hard enough.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Crumble {
+    /// The entity holding this object's collider, which may be a child of this entity.
+    pub collider: Entity,
+    /// Seconds of contact with the player before the object starts shaking.
+    pub delay: f32,
+    /// Contact force that makes the object start shaking immediately.
+    pub force_threshold: Option<f32>,
+    /// Seconds the object shakes before crumbling.
+    pub shake_time: f32,
+    pub mode: CrumbleMode,
+    /// Seconds after crumbling before the object comes back, if it comes back at all.
+    pub respawn: Option<f32>,
+    /// Where the object returns to when it respawns.
+    pub origin: Transform,
+    /// The body type the object returns to when it respawns.
+    pub rigid_body: RigidBody,
+    pub state: CrumbleState,
+}
+
+/// Checks the contacts from this tick's physics step, so it runs after the step and doesn't depend
+/// on the frame rate.
+fn detect_crumble_contacts(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    player: Query<Entity, With<Player>>,
+    mut crumbles: Query<&mut Crumble>,
+) {
+    let player_entity = player.single();
+
+    for mut crumble in crumbles.iter_mut() {
+        let CrumbleState::Intact { contact_time } = crumble.state else {
+            continue;
+        };
+
+        let Some(pair) = rapier_context.contact_pair(player_entity, crumble.collider) else {
+            continue;
+        };
+
+        if !pair.has_any_active_contacts() {
+            continue;
+        }
+
+        // the solver's impulses over the step, turned back into the force it applied
+        let impulse: f32 = pair
+            .manifolds()
+            .flat_map(|manifold| manifold.points().map(|point| point.impulse()))
+            .sum();
+        let force = impulse / time.delta_seconds();
+
+        let contact_time = contact_time + time.delta_seconds();
+        let hit_hard = crumble
+            .force_threshold
+            .map_or(false, |threshold| force >= threshold);
+
+        crumble.state = if hit_hard || contact_time >= crumble.delay {
+            CrumbleState::Shaking { time: 0.0 }
+        } else {
+            CrumbleState::Intact { contact_time }
+        };
+    }
+}
+
+fn update_crumbling(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut crumbles: Query<(
+        &mut Crumble,
+        &mut Transform,
+        &mut RigidBody,
+        &mut Visibility,
+        Option<&mut Velocity>,
+    )>,
+) {
+    for (mut crumble, mut transform, mut rigid_body, mut visibility, velocity) in
+        crumbles.iter_mut()
+    {
+        match crumble.state {
+            CrumbleState::Intact { .. } => {}
+            CrumbleState::Shaking { time: shaking } => {
+                let shaking = shaking + time.delta_seconds();
+
+                if shaking >= crumble.shake_time {
+                    transform.translation = crumble.origin.translation;
+
+                    match crumble.mode {
+                        CrumbleMode::Fall => *rigid_body = RigidBody::Dynamic,
+                        CrumbleMode::Vanish => {
+                            commands.entity(crumble.collider).insert(ColliderDisabled);
+                            *visibility = Visibility::Hidden;
+                        }
+                    }
+
+                    crumble.state = CrumbleState::Gone { time: 0.0 };
+                } else {
+                    let t = time.elapsed_seconds() * 60.0;
+                    let offset = Vec3::new(t.sin(), (t * 1.3).cos(), (t * 0.7).sin());
+                    transform.translation = crumble.origin.translation + offset * SHAKE_AMOUNT;
+
+                    crumble.state = CrumbleState::Shaking { time: shaking };
+                }
+            }
+            CrumbleState::Gone { time: gone } => {
+                let Some(respawn) = crumble.respawn else {
+                    continue;
+                };
+
+                let gone = gone + time.delta_seconds();
+
+                if gone >= respawn {
+                    *transform = crumble.origin;
+                    *rigid_body = crumble.rigid_body;
+                    *visibility = Visibility::Inherited;
+                    if let Some(mut velocity) = velocity {
+                        *velocity = Velocity::zero();
+                    }
+                    commands
+                        .entity(crumble.collider)
+                        .remove::<ColliderDisabled>();
+
+                    crumble.state = CrumbleState::Intact { contact_time: 0.0 };
+                } else {
+                    crumble.state = CrumbleState::Gone { time: gone };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Checkpoints along the way and the goal at the end of a level.
+
+use crate::level::LevelLoadedEvent;
+use crate::player::{player_exists, Player};
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.init_resource::<ActiveCheckpoint>()
+        .add_event::<CheckpointReachedEvent>()
+        .add_event::<LevelCompletedEvent>()
+        .add_systems(Update, reset_checkpoint)
+        .add_systems(
+            FixedUpdate,
+            // right after the physics step, so the timer splits on the tick the sensor was entered
+            (reach_checkpoints, reach_goal)
+                .after(PhysicsSet::Writeback)
+                .run_if(player_exists)
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_systems(Update, complete_level.run_if(in_state(AppState::InGame)));
+}
+
+/// A sensor that becomes the player's respawn point once they roll through it.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct Checkpoint {
+    pub reached: bool,
+    /// Where the player respawns, above the checkpoint so the ball isn't put inside the floor.
+    pub respawn: Vec3,
+}
+
+/// A sensor that completes the level when the player reaches it.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct Goal {
+    pub reached: bool,
+}
+
+/// The checkpoint the player respawns at, if they've reached any.
+#[derive(Default, Debug, Copy, Clone, Resource)]
+pub struct ActiveCheckpoint {
+    pub checkpoint: Option<Entity>,
+}
+
+/// Sent the first time the player reaches each checkpoint.
+#[derive(Debug, Copy, Clone, Event)]
+pub struct CheckpointReachedEvent {
+    pub checkpoint: Entity,
+}
+
+/// Sent when the player reaches the goal.
+#[derive(Default, Debug, Copy, Clone, Event)]
+pub struct LevelCompletedEvent;
+
+fn reset_checkpoint(
+    mut active: ResMut<ActiveCheckpoint>,
+    mut level_load: EventReader<LevelLoadedEvent>,
+) {
+    if level_load.read().next().is_some() {
+        active.checkpoint = None;
+    }
+
+    level_load.clear();
+}
+
+pub fn reach_checkpoints(
+    player: Query<Entity, With<Player>>,
+    mut checkpoints: Query<(Entity, &mut Checkpoint)>,
+    mut active: ResMut<ActiveCheckpoint>,
+    rapier_context: Res<RapierContext>,
+    mut reached_events: EventWriter<CheckpointReachedEvent>,
+) {
+    let player_entity = player.single();
+
+    for (entity, mut checkpoint) in checkpoints.iter_mut() {
+        if !checkpoint.reached
+            && rapier_context.intersection_pair(player_entity, entity) == Some(true)
+        {
+            checkpoint.reached = true;
+            active.checkpoint = Some(entity);
+            reached_events.send(CheckpointReachedEvent { checkpoint: entity });
+
+            info!("Checkpoint reached.");
+        }
+    }
+}
+
+pub fn reach_goal(
+    player: Query<Entity, With<Player>>,
+    mut goals: Query<(Entity, &mut Goal)>,
+    rapier_context: Res<RapierContext>,
+    mut completed_events: EventWriter<LevelCompletedEvent>,
+) {
+    let player_entity = player.single();
+
+    for (entity, mut goal) in goals.iter_mut() {
+        if !goal.reached && rapier_context.intersection_pair(player_entity, entity) == Some(true) {
+            goal.reached = true;
+            completed_events.send(LevelCompletedEvent);
+
+            info!("Level completed.");
+
+            break;
+        }
+    }
+}
+
+fn complete_level(
+    mut completed_events: EventReader<LevelCompletedEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if completed_events.read().next().is_some() {
+        next_state.set(AppState::LevelComplete);
+    }
+
+    completed_events.clear();
+}
@@ -0,0 +1,110 @@
+//! Hazards that hurt the player.
+
+use crate::player::{player_exists, Health, Invulnerability, Player};
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (tick_invulnerability, apply_hazards)
+            .chain()
+            .after(PhysicsSet::Writeback)
+            .run_if(player_exists)
+            .run_if(in_state(AppState::InGame)),
+    )
+    .add_systems(
+        FixedUpdate,
+        rotate_objects.run_if(in_state(AppState::InGame)),
+    );
+}
+
+/// Seconds the player can't be hit again after taking a hit.
+pub const INVULNERABILITY_TIME: f32 = 1.0;
+
+/// How a hazard hurts the player.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HazardDamage {
+    /// Takes away this much health on contact, followed by invulnerability.
+    Hit(f32),
+    /// Takes away this much health per second while the player is in contact.
+    Burn(f32),
+}
+
+/// A collider that damages the player while they touch it.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Hazard {
+    pub damage: HazardDamage,
+}
+
+/// A kinematic object that constantly rotates around its local Y axis, like a spinning blade.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Rotator {
+    /// Radians per second
+    pub speed: f32,
+}
+
+fn tick_invulnerability(
+    time: Res<Time>,
+    mut player: Query<(&mut Invulnerability, &mut Visibility), With<Player>>,
+) {
+    let (mut invulnerability, mut visibility) = player.single_mut();
+
+    if invulnerability.remaining > 0.0 {
+        invulnerability.remaining = (invulnerability.remaining - time.delta_seconds()).max(0.0);
+    }
+
+    // blink while invulnerable
+    let blink =
+        invulnerability.remaining > 0.0 && (invulnerability.remaining * 10.0) as u32 % 2 == 1;
+    let target = if blink {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    if *visibility != target {
+        *visibility = target;
+    }
+}
+
+pub fn apply_hazards(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut player: Query<(Entity, &mut Health, &mut Invulnerability), With<Player>>,
+    hazards: Query<(Entity, &Hazard, Has<Sensor>)>,
+) {
+    let (player_entity, mut health, mut invulnerability) = player.single_mut();
+
+    for (hazard_entity, hazard, sensor) in hazards.iter() {
+        let touching = if sensor {
+            rapier_context.intersection_pair(player_entity, hazard_entity) == Some(true)
+        } else {
+            rapier_context
+                .contact_pair(player_entity, hazard_entity)
+                .map_or(false, |pair| pair.has_any_active_contacts())
+        };
+
+        if !touching {
+            continue;
+        }
+
+        match hazard.damage {
+            HazardDamage::Hit(amount) => {
+                if invulnerability.remaining <= 0.0 {
+                    health.current -= amount;
+                    invulnerability.remaining = INVULNERABILITY_TIME;
+                }
+            }
+            HazardDamage::Burn(per_second) => {
+                health.current -= per_second * time.delta_seconds();
+            }
+        }
+    }
+}
+
+fn rotate_objects(time: Res<Time>, mut rotators: Query<(&Rotator, &mut Transform)>) {
+    for (rotator, mut transform) in rotators.iter_mut() {
+        transform.rotate_local_y(rotator.speed * time.delta_seconds());
+    }
+}
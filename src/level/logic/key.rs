@@ -0,0 +1,101 @@
+//! Keys the player carries and the locks they open.
+
+use crate::level::logic::pickup::{collect_pickups, PickupEvent, PickupKind};
+use crate::level::LevelRemovedEvent;
+use crate::player::{player_exists, Player};
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.init_resource::<Inventory>()
+        .add_systems(Update, reset_inventory)
+        .add_systems(
+            FixedUpdate,
+            (collect_keys, open_locks)
+                .chain()
+                .after(collect_pickups)
+                .run_if(player_exists)
+                .run_if(in_state(AppState::InGame)),
+        );
+}
+
+#[derive(Debug, Clone)]
+pub struct HeldKey {
+    pub id: String,
+    pub color: Color,
+}
+
+/// The keys the player has collected in the current level.
+#[derive(Default, Debug, Clone, Resource)]
+pub struct Inventory {
+    pub keys: Vec<HeldKey>,
+}
+
+impl Inventory {
+    pub fn has_key(&self, id: &str) -> bool {
+        self.keys.iter().any(|key| key.id == id)
+    }
+}
+
+/// A solid object that disappears when the player touches it while carrying the matching key.
+#[derive(Debug, Clone, Component)]
+pub struct Lock {
+    pub key: String,
+}
+
+fn reset_inventory(
+    mut inventory: ResMut<Inventory>,
+    mut level_remove: EventReader<LevelRemovedEvent>,
+) {
+    if level_remove.read().next().is_some() {
+        inventory.keys.clear();
+    }
+
+    level_remove.clear();
+}
+
+fn collect_keys(mut inventory: ResMut<Inventory>, mut events: EventReader<PickupEvent>) {
+    for event in events.read() {
+        if let PickupKind::Key { id, color } = &event.kind {
+            if !inventory.has_key(id) {
+                info!("Collected key \"{}\".", id);
+
+                inventory.keys.push(HeldKey {
+                    id: id.clone(),
+                    color: *color,
+                });
+            }
+        }
+    }
+}
+
+fn open_locks(
+    mut commands: Commands,
+    inventory: Res<Inventory>,
+    player: Query<Entity, With<Player>>,
+    locks: Query<&Lock>,
+    mut events: EventReader<CollisionEvent>,
+) {
+    let player_entity = player.single();
+
+    for event in events.read() {
+        if let CollisionEvent::Started(entity_a, entity_b, _flags) = *event {
+            let other = if player_entity == entity_a {
+                entity_b
+            } else if player_entity == entity_b {
+                entity_a
+            } else {
+                continue;
+            };
+
+            if let Ok(lock) = locks.get(other) {
+                if inventory.has_key(&lock.key) {
+                    info!("Opened lock \"{}\".", lock.key);
+
+                    commands.entity(other).despawn_recursive();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Logic for level objects.
+
+pub mod crumble;
+pub mod goal;
+pub mod hazard;
+pub mod key;
+pub mod pickup;
+pub mod signal;
+
+use crate::level::logic::goal::{ActiveCheckpoint, Checkpoint};
+use crate::level::PlayerSpawnPoint;
+use crate::player::{player_exists, Health, Invulnerability, Player};
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.add_event::<PlayerDiedEvent>().add_systems(
+        FixedUpdate,
+        (player_death, respawn_player)
+            .chain()
+            .after(PhysicsSet::Writeback)
+            .after(hazard::apply_hazards)
+            .after(goal::reach_checkpoints)
+            .run_if(player_exists)
+            .run_if(in_state(AppState::InGame)),
+    );
+    signal::setup(app);
+    pickup::setup(app);
+    key::setup(app);
+    crumble::setup(app);
+    hazard::setup(app);
+    goal::setup(app);
+}
+
+/// An object that kills the player and resets the player's position to the spawn point.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct DeathObject;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DeathCause {
+    /// The player touched a [`DeathObject`].
+    DeathObject,
+    /// The player ran out of health.
+    Health,
+}
+
+/// Sent when the player dies, before they are moved back to the spawn point.
+#[derive(Debug, Copy, Clone, Event)]
+pub struct PlayerDiedEvent {
+    pub cause: DeathCause,
+}
+
+/// Actually handles death object collision and running out of health.
+fn player_death(
+    player: Query<(Entity, &Health), With<Player>>,
+    death_objects: Query<(), With<DeathObject>>,
+    mut events: EventReader<CollisionEvent>,
+    mut died_events: EventWriter<PlayerDiedEvent>,
+) {
+    let (player_entity, health) = player.single();
+
+    let mut cause = None;
+
+    for event in events.read() {
+        if let CollisionEvent::Started(entity_a, entity_b, _flags) = *event {
+            if player_entity == entity_a || player_entity == entity_b {
+                if death_objects.contains(entity_a) || death_objects.contains(entity_b) {
+                    cause = Some(DeathCause::DeathObject);
+                    break;
+                }
+            }
+        }
+    }
+
+    events.clear();
+
+    if cause.is_none() && health.current <= 0.0 {
+        cause = Some(DeathCause::Health);
+    }
+
+    if let Some(cause) = cause {
+        died_events.send(PlayerDiedEvent { cause });
+    }
+}
+
+/// Moves the player back to the last checkpoint or the spawn point after they die.
+fn respawn_player(
+    mut player: Query<
+        (
+            &mut Transform,
+            &mut Velocity,
+            &mut Health,
+            &mut Invulnerability,
+        ),
+        (With<Player>, Without<PlayerSpawnPoint>),
+    >,
+    spawnpoint: Query<&Transform, (With<PlayerSpawnPoint>, Without<Player>)>,
+    checkpoints: Query<&Checkpoint>,
+    active_checkpoint: Res<ActiveCheckpoint>,
+    mut died_events: EventReader<PlayerDiedEvent>,
+) {
+    if died_events.read().next().is_some() {
+        let (mut player_transform, mut player_velocity, mut health, mut invulnerability) =
+            player.single_mut();
+        let spawnpoint = match active_checkpoint
+            .checkpoint
+            .and_then(|checkpoint| checkpoints.get(checkpoint).ok())
+        {
+            Some(checkpoint) => checkpoint.respawn,
+            None => spawnpoint.single().translation,
+        };
+
+        player_transform.translation = spawnpoint;
+        player_velocity.angvel = Vec3::ZERO;
+        player_velocity.linvel = Vec3::ZERO;
+        health.current = health.max;
+        invulnerability.remaining = 0.0;
+    }
+
+    died_events.clear();
+}
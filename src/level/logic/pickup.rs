@@ -0,0 +1,86 @@
+//! Objects the player collects by rolling through them.
+
+use crate::player::ball::BallType;
+use crate::player::{player_exists, Player};
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.add_event::<PickupEvent>()
+        .add_systems(
+            FixedUpdate,
+            collect_pickups
+                .after(PhysicsSet::Writeback)
+                .run_if(player_exists)
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_systems(Update, spin_pickups);
+}
+
+/// What the player gets for collecting a pickup.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PickupKind {
+    Key {
+        id: String,
+        color: Color,
+    },
+    ExtraLife,
+    /// Something hidden in the level that the save profile remembers finding.
+    Collectible {
+        id: String,
+    },
+    /// Turns the player into another type of ball.
+    Ball(BallType),
+}
+
+/// A sensor that is removed and sends a [`PickupEvent`] when the player touches it.
+#[derive(Debug, Clone, Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+}
+
+/// Sent when the player collects a pickup.
+#[derive(Debug, Clone, Event)]
+pub struct PickupEvent {
+    pub kind: PickupKind,
+}
+
+/// Slowly rotates pickups so they stand out from the level.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct Spin;
+
+pub fn collect_pickups(
+    mut commands: Commands,
+    player: Query<Entity, With<Player>>,
+    pickups: Query<&Pickup>,
+    mut events: EventReader<CollisionEvent>,
+    mut pickup_events: EventWriter<PickupEvent>,
+) {
+    let player_entity = player.single();
+
+    for event in events.read() {
+        if let CollisionEvent::Started(entity_a, entity_b, _flags) = *event {
+            let other = if player_entity == entity_a {
+                entity_b
+            } else if player_entity == entity_b {
+                entity_a
+            } else {
+                continue;
+            };
+
+            if let Ok(pickup) = pickups.get(other) {
+                pickup_events.send(PickupEvent {
+                    kind: pickup.kind.clone(),
+                });
+                commands.entity(other).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn spin_pickups(time: Res<Time>, mut pickups: Query<&mut Transform, With<Spin>>) {
+    for mut transform in pickups.iter_mut() {
+        transform.rotate_y(time.delta_seconds());
+    }
+}
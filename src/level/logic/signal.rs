@@ -0,0 +1,269 @@
+//! Switches, signals and the objects that listen to them.
+
+use crate::player::Player;
+use crate::AppState;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_rapier3d::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.init_resource::<Signals>().add_systems(
+        FixedUpdate,
+        (
+            update_switches,
+            update_signals,
+            update_receivers,
+            (
+                update_doors,
+                update_movers,
+                update_force_zones,
+                update_lights,
+            ),
+        )
+            .chain()
+            .run_if(in_state(AppState::InGame)),
+    );
+}
+
+/// The set of signals currently being emitted by switches in the level.
+#[derive(Default, Debug, Clone, Resource)]
+pub struct Signals {
+    active: HashSet<String>,
+    previous: HashSet<String>,
+}
+
+impl Signals {
+    pub fn is_active(&self, signal: &str) -> bool {
+        self.active.contains(signal)
+    }
+
+    /// Whether the signal became active this tick.
+    pub fn just_activated(&self, signal: &str) -> bool {
+        self.active.contains(signal) && !self.previous.contains(signal)
+    }
+}
+
+/// What kind of bodies are able to press a switch.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum SwitchActivator {
+    /// Only the player can press the switch, like a pressure plate.
+    Player,
+    /// Only dynamic objects that aren't the player can press the switch.
+    Cube,
+    /// Any dynamic object, including the player, can press the switch.
+    #[default]
+    Any,
+}
+
+/// A sensor that emits a signal while something is resting on it.
+#[derive(Debug, Clone, Component)]
+pub struct Switch {
+    pub signal: String,
+    pub activator: SwitchActivator,
+    pub pressed: bool,
+}
+
+/// How a listener reacts to its signal.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SignalAction {
+    /// The object is enabled while the signal is active.
+    Enable,
+    /// The object is disabled while the signal is active.
+    Disable,
+    /// The object flips its state each time the signal becomes active.
+    Toggle,
+}
+
+#[derive(Debug, Clone)]
+pub struct SignalListener {
+    pub signal: String,
+    pub action: SignalAction,
+}
+
+/// An object whose enabled state is controlled by signals.
+#[derive(Debug, Clone, Component)]
+pub struct SignalReceiver {
+    pub listeners: Vec<SignalListener>,
+    /// The state the object has when no enable or disable signals are active.
+    pub base: bool,
+    pub enabled: bool,
+}
+
+impl SignalReceiver {
+    pub fn new(listeners: Vec<SignalListener>, enabled: bool) -> SignalReceiver {
+        SignalReceiver {
+            listeners,
+            base: enabled,
+            enabled,
+        }
+    }
+}
+
+/// A solid wall that opens while it is enabled.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct Door;
+
+/// A kinematic platform that moves back and forth between two points while it is enabled.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Mover {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub speed: f32,
+    pub progress: f32,
+    pub forward: bool,
+}
+
+/// A sensor that accelerates dynamic bodies inside it while it is enabled.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct ForceZone {
+    pub acceleration: Vec3,
+}
+
+/// A light that is only lit while it is enabled.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct LevelLight {
+    pub intensity: f32,
+}
+
+/// Figures out which switches have something resting on them.
+fn update_switches(
+    mut switches: Query<(&mut Switch, &CollidingEntities)>,
+    bodies: Query<(&RigidBody, Has<Player>)>,
+) {
+    for (mut switch, colliding) in switches.iter_mut() {
+        let pressed = colliding.iter().any(|entity| match bodies.get(entity) {
+            Ok((&RigidBody::Dynamic, is_player)) => match switch.activator {
+                SwitchActivator::Player => is_player,
+                SwitchActivator::Cube => !is_player,
+                SwitchActivator::Any => true,
+            },
+            _ => false,
+        });
+
+        if switch.pressed != pressed {
+            switch.pressed = pressed;
+        }
+    }
+}
+
+/// Collects the signals emitted by pressed switches.
+fn update_signals(mut signals: ResMut<Signals>, switches: Query<&Switch>) {
+    let signals = &mut *signals;
+    std::mem::swap(&mut signals.active, &mut signals.previous);
+    signals.active.clear();
+
+    for switch in switches.iter() {
+        if switch.pressed {
+            signals.active.insert(switch.signal.clone());
+        }
+    }
+}
+
+/// Applies the current signals to everything listening to them.
+fn update_receivers(signals: Res<Signals>, mut receivers: Query<&mut SignalReceiver>) {
+    for mut receiver in receivers.iter_mut() {
+        let mut base = receiver.base;
+        for listener in receiver.listeners.iter() {
+            if listener.action == SignalAction::Toggle && signals.just_activated(&listener.signal) {
+                base = !base;
+            }
+        }
+
+        let mut enabled = base;
+        for listener in receiver.listeners.iter() {
+            if signals.is_active(&listener.signal) {
+                match listener.action {
+                    SignalAction::Enable => enabled = true,
+                    SignalAction::Disable => enabled = false,
+                    SignalAction::Toggle => {}
+                }
+            }
+        }
+
+        if receiver.base != base || receiver.enabled != enabled {
+            receiver.base = base;
+            receiver.enabled = enabled;
+        }
+    }
+}
+
+fn update_doors(
+    mut commands: Commands,
+    mut doors: Query<
+        (Entity, &SignalReceiver, &mut Visibility),
+        (With<Door>, Changed<SignalReceiver>),
+    >,
+) {
+    for (entity, receiver, mut visibility) in doors.iter_mut() {
+        if receiver.enabled {
+            commands.entity(entity).insert(ColliderDisabled);
+            *visibility = Visibility::Hidden;
+        } else {
+            commands.entity(entity).remove::<ColliderDisabled>();
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+fn update_movers(
+    time: Res<Time>,
+    mut movers: Query<(&mut Mover, &mut Transform, &SignalReceiver)>,
+) {
+    for (mut mover, mut transform, receiver) in movers.iter_mut() {
+        if !receiver.enabled {
+            continue;
+        }
+
+        let length = mover.start.distance(mover.end);
+        if length <= f32::EPSILON {
+            continue;
+        }
+
+        let step = mover.speed * time.delta_seconds() / length;
+        if mover.forward {
+            mover.progress += step;
+            if mover.progress >= 1.0 {
+                mover.progress = 1.0;
+                mover.forward = false;
+            }
+        } else {
+            mover.progress -= step;
+            if mover.progress <= 0.0 {
+                mover.progress = 0.0;
+                mover.forward = true;
+            }
+        }
+
+        transform.translation = mover.start.lerp(mover.end, mover.progress);
+    }
+}
+
+fn update_force_zones(
+    time: Res<Time>,
+    zones: Query<(&ForceZone, &SignalReceiver, &CollidingEntities)>,
+    mut bodies: Query<&mut Velocity>,
+) {
+    for (zone, receiver, colliding) in zones.iter() {
+        if !receiver.enabled {
+            continue;
+        }
+
+        for entity in colliding.iter() {
+            if let Ok(mut velocity) = bodies.get_mut(entity) {
+                velocity.linvel += zone.acceleration * time.delta_seconds();
+            }
+        }
+    }
+}
+
+fn update_lights(
+    mut lights: Query<(&LevelLight, &SignalReceiver, &mut PointLight), Changed<SignalReceiver>>,
+) {
+    for (light, receiver, mut point_light) in lights.iter_mut() {
+        point_light.intensity = if receiver.enabled {
+            light.intensity
+        } else {
+            0.0
+        };
+    }
+}
@@ -2,6 +2,9 @@ pub mod logic;
 pub mod serial;
 
 use crate::level::serial::{LevelAssetLoader, SerialLevel, SpawnArgs};
+use crate::player::abilities::AbilityKind;
+use crate::player::ball::BallType;
+use crate::player::config::SerialPlayerConfig;
 use bevy::prelude::*;
 
 pub struct LevelsPlugin;
@@ -12,9 +15,13 @@ impl Plugin for LevelsPlugin {
             .init_resource::<LevelStateOld>()
             .add_event::<LevelRemovedEvent>()
             .add_event::<LevelLoadedEvent>()
+            .add_event::<RestartLevelEvent>()
             .init_asset::<SerialLevel>()
             .init_asset_loader::<LevelAssetLoader>()
-            .add_systems(PreUpdate, (remove_level, build_level_on_load));
+            .add_systems(
+                PreUpdate,
+                (remove_level, restart_level, build_level_on_load).chain(),
+            );
         logic::setup(app);
     }
 }
@@ -27,17 +34,46 @@ pub struct LevelState {
 #[derive(Default, Debug, Clone, Resource)]
 pub struct LevelStateOld {
     pub handle: Option<Handle<SerialLevel>>,
+    /// Set when the level has been torn down and needs to be built again.
+    pub rebuild: bool,
 }
 
 #[derive(Default, Debug, Copy, Clone, Component)]
 pub struct LevelObject;
 
-#[derive(Default, Debug, Copy, Clone, Component)]
-pub struct PlayerSpawnPoint;
+#[derive(Debug, Clone, Component)]
+pub struct PlayerSpawnPoint {
+    /// The health the player spawns with in this level.
+    pub max_health: f32,
+    /// The number of lives the player has in this level, or `None` for unlimited lives.
+    pub lives: Option<u32>,
+    /// Changes to the player's config in this level.
+    pub player: SerialPlayerConfig,
+    /// The ball the player has to use in this level, instead of the one they picked.
+    pub ball: Option<BallType>,
+    /// The abilities the player can use in this level.
+    pub abilities: Vec<AbilityKind>,
+}
+
+impl Default for PlayerSpawnPoint {
+    fn default() -> Self {
+        PlayerSpawnPoint {
+            max_health: 3.0,
+            lives: None,
+            player: SerialPlayerConfig::default(),
+            ball: None,
+            abilities: vec![],
+        }
+    }
+}
 
 #[derive(Default, Debug, Copy, Clone, Event)]
 pub struct LevelRemovedEvent;
 
+/// Send to tear down the current level and build it again from scratch.
+#[derive(Default, Debug, Copy, Clone, Event)]
+pub struct RestartLevelEvent;
+
 #[derive(Debug, Copy, Clone, Event)]
 pub struct LevelLoadedEvent {
     pub entities: LevelPertinentEntities,
@@ -46,6 +82,8 @@ pub struct LevelLoadedEvent {
 #[derive(Debug, Copy, Clone)]
 pub struct LevelPertinentEntities {
     pub spawn: Entity,
+    /// The level's intro [`Flyover`](crate::intro::Flyover), if it has one.
+    pub intro: Option<Entity>,
 }
 
 /// Removes all level objects if the level is set to None.
@@ -71,6 +109,34 @@ fn remove_level(
     }
 }
 
+/// Removes all level objects so they can be rebuilt on the next frame.
+///
+/// The rebuild is delayed so that everything listening for [`LevelRemovedEvent`] gets to clean up
+/// before the [`LevelLoadedEvent`] arrives.
+fn restart_level(
+    mut commands: Commands,
+    mut restart_events: EventReader<RestartLevelEvent>,
+    mut level_state_old: ResMut<LevelStateOld>,
+    mut level_events: EventWriter<LevelRemovedEvent>,
+    old_objects: Query<Entity, With<LevelObject>>,
+) {
+    if restart_events.read().next().is_some() {
+        if level_state_old.handle.is_some() {
+            for old in old_objects.iter() {
+                commands.entity(old).despawn_recursive();
+            }
+
+            level_state_old.rebuild = true;
+
+            level_events.send(LevelRemovedEvent);
+
+            info!("Level restarting.");
+        }
+    }
+
+    restart_events.clear();
+}
+
 /// Adds level objects once the given level has loaded, re-adding if reloaded.
 fn build_level_on_load(
     level_state: Res<LevelState>,
@@ -80,10 +146,13 @@ fn build_level_on_load(
     mut asset_events: EventReader<AssetEvent<SerialLevel>>,
     assets: Res<Assets<SerialLevel>>,
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: Option<ResMut<Assets<Mesh>>>,
+    mut materials: Option<ResMut<Assets<StandardMaterial>>>,
 ) {
     if let Some(level_handle) = &level_state.handle {
+        // wait a frame after a restart tore the level down before building it again
+        let mut build = level_state_old.rebuild && !level_state_old.is_changed();
+
         for event in asset_events.read() {
             let (&handle, update) = match event {
                 AssetEvent::Added { id } => (id, false),
@@ -93,26 +162,29 @@ fn build_level_on_load(
             };
 
             if handle == level_handle.id() && update {
-                if let Some(level) = assets.get(level_handle) {
-                    // remove old objects if they're still around
-                    for old in old_objects.iter() {
-                        commands.entity(old).despawn_recursive();
-                    }
+                build = true;
+            }
+        }
 
-                    level_state_old.handle = Some(level_handle.clone());
+        if build {
+            if let Some(level) = assets.get(level_handle) {
+                // remove old objects if they're still around
+                for old in old_objects.iter() {
+                    commands.entity(old).despawn_recursive();
+                }
 
-                    let entities = level.spawn(&mut SpawnArgs {
-                        commands: &mut commands,
-                        meshes: &mut meshes,
-                        materials: &mut materials,
-                    });
+                level_state_old.handle = Some(level_handle.clone());
+                level_state_old.rebuild = false;
 
-                    level_events.send(LevelLoadedEvent { entities });
+                let entities = level.spawn(&mut SpawnArgs {
+                    commands: &mut commands,
+                    meshes: meshes.as_deref_mut(),
+                    materials: materials.as_deref_mut(),
+                });
 
-                    info!("Level Loaded.");
+                level_events.send(LevelLoadedEvent { entities });
 
-                    break;
-                }
+                info!("Level Loaded.");
             }
         }
     }
@@ -1,9 +1,26 @@
+use crate::intro::{Flyover, FlyoverPoint};
+use crate::level::logic::crumble::{Crumble, CrumbleMode, CrumbleState};
+use crate::level::logic::goal::{Checkpoint, Goal};
+use crate::level::logic::hazard::{Hazard, HazardDamage, Rotator};
+use crate::level::logic::key::Lock;
+use crate::level::logic::pickup::{Pickup, PickupKind, Spin};
+use crate::level::logic::signal::{
+    Door, ForceZone, LevelLight, Mover, SignalAction, SignalListener, SignalReceiver, Switch,
+    SwitchActivator,
+};
 use crate::level::logic::DeathObject;
 use crate::level::{LevelObject, LevelPertinentEntities, PlayerSpawnPoint};
+use crate::player::abilities::AbilityKind;
+use crate::player::ball::BallType;
+use crate::player::camera::{CameraPlacement, CameraZone};
+use crate::player::config::SerialPlayerConfig;
+use crate::util::fnv1a;
 use bevy::asset::io::Reader;
 use bevy::asset::{AssetLoader, AsyncReadExt, BoxedFuture, LoadContext};
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 use bevy::reflect::TypeUuid;
+use bevy::utils::HashSet;
 use bevy_rapier3d::prelude::*;
 use std::f32::consts::PI;
 
@@ -33,6 +50,8 @@ impl AssetLoader for LevelAssetLoader {
                     }
                 };
 
+            level.validate()?;
+
             Ok(level)
         })
     }
@@ -42,10 +61,31 @@ impl AssetLoader for LevelAssetLoader {
     }
 }
 
-pub struct SpawnArgs<'a, 'w, 's, 'r1, 'r2> {
+pub struct SpawnArgs<'a, 'w, 's> {
     pub commands: &'a mut Commands<'w, 's>,
-    pub meshes: &'a mut ResMut<'r1, Assets<Mesh>>,
-    pub materials: &'a mut ResMut<'r2, Assets<StandardMaterial>>,
+    /// `None` when running without a renderer.
+    pub meshes: Option<&'a mut Assets<Mesh>>,
+    /// `None` when running without a renderer.
+    pub materials: Option<&'a mut Assets<StandardMaterial>>,
+}
+
+impl SpawnArgs<'_, '_, '_> {
+    /// Adds a mesh for an object, or gives a placeholder handle when running without a renderer.
+    pub fn mesh(&mut self, mesh: Mesh) -> Handle<Mesh> {
+        match &mut self.meshes {
+            Some(meshes) => meshes.add(mesh),
+            None => Handle::default(),
+        }
+    }
+
+    /// Adds a material for an object, or gives a placeholder handle when running without a
+    /// renderer.
+    pub fn material(&mut self, material: StandardMaterial) -> Handle<StandardMaterial> {
+        match &mut self.materials {
+            Some(materials) => materials.add(material),
+            None => Handle::default(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, knuffel::Decode, TypeUuid, Asset, TypePath)]
@@ -54,17 +94,149 @@ pub struct SerialLevel {
     #[knuffel(child)]
     spawn: SerialSpawnPoint,
 
+    /// A camera flyover played before the level starts
+    #[knuffel(child)]
+    intro: Option<SerialIntro>,
+
     #[knuffel(children(name = "cube"))]
     cubes: Vec<SerialCube>,
 
     #[knuffel(children(name = "plane"))]
     planes: Vec<SerialPlane>,
 
+    #[knuffel(children(name = "box"))]
+    boxes: Vec<SerialBox>,
+
     #[knuffel(children(name = "death_plane"))]
     death_planes: Vec<SerialDeathPlane>,
+
+    #[knuffel(children(name = "switch"))]
+    switches: Vec<SerialSwitch>,
+
+    #[knuffel(children(name = "door"))]
+    doors: Vec<SerialDoor>,
+
+    #[knuffel(children(name = "mover"))]
+    movers: Vec<SerialMover>,
+
+    #[knuffel(children(name = "force_zone"))]
+    force_zones: Vec<SerialForceZone>,
+
+    #[knuffel(children(name = "light"))]
+    lights: Vec<SerialLight>,
+
+    #[knuffel(children(name = "camera_zone"))]
+    camera_zones: Vec<SerialCameraZone>,
+
+    #[knuffel(children(name = "key"))]
+    keys: Vec<SerialKey>,
+
+    #[knuffel(children(name = "lock"))]
+    locks: Vec<SerialLock>,
+
+    #[knuffel(children(name = "extra_life"))]
+    extra_lives: Vec<SerialExtraLife>,
+
+    #[knuffel(children(name = "collectible"))]
+    collectibles: Vec<SerialCollectible>,
+
+    #[knuffel(children(name = "ball_pickup"))]
+    ball_pickups: Vec<SerialBallPickup>,
+
+    #[knuffel(children(name = "checkpoint"))]
+    checkpoints: Vec<SerialCheckpoint>,
+
+    #[knuffel(children(name = "goal"))]
+    goals: Vec<SerialGoal>,
+
+    /// Asset path of the level that completing this one unlocks
+    #[knuffel(child, unwrap(argument))]
+    next: Option<String>,
 }
 
 impl SerialLevel {
+    /// A hash of the level's contents, used to check that replays are played on the same level
+    /// they were recorded on.
+    pub fn content_hash(&self) -> u64 {
+        fnv1a(format!("{:?}", self).as_bytes())
+    }
+
+    /// Asset path of the level that completing this one unlocks.
+    pub fn next_level(&self) -> Option<&str> {
+        self.next.as_deref()
+    }
+
+    /// Checks references between nodes, like signals being listened to.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let emitted: HashSet<&str> = self
+            .switches
+            .iter()
+            .map(|switch| switch.signal.as_str())
+            .collect();
+
+        let listeners = self
+            .doors
+            .iter()
+            .flat_map(|door| door.listeners.iter())
+            .chain(self.movers.iter().flat_map(|mover| mover.listeners.iter()))
+            .chain(
+                self.force_zones
+                    .iter()
+                    .flat_map(|zone| zone.listeners.iter()),
+            )
+            .chain(self.lights.iter().flat_map(|light| light.listeners.iter()));
+
+        for listener in listeners {
+            if !emitted.contains(listener.signal.as_str()) {
+                anyhow::bail!(
+                    "Signal \"{}\" is listened to but no switch emits it",
+                    listener.signal
+                );
+            }
+        }
+
+        let keys: HashSet<&str> = self.keys.iter().map(|key| key.id.as_str()).collect();
+        for lock in self.locks.iter() {
+            if !keys.contains(lock.key.as_str()) {
+                anyhow::bail!(
+                    "Lock needs key \"{}\" but the level has no such key",
+                    lock.key
+                );
+            }
+        }
+
+        if let Some(intro) = &self.intro {
+            if intro.points.is_empty() {
+                anyhow::bail!("Intro has no points to fly through");
+            }
+            if intro.duration <= 0.0 {
+                anyhow::bail!("Intro has to last longer than 0 seconds");
+            }
+        }
+
+        for zone in self.camera_zones.iter() {
+            if zone.fixed.is_some() && zone.rail.is_some() {
+                anyhow::bail!("Camera zone has both a fixed position and a rail");
+            }
+            if zone
+                .rail
+                .as_ref()
+                .is_some_and(|rail| rail.points.is_empty())
+            {
+                anyhow::bail!("Camera zone has a rail with no points");
+            }
+        }
+
+        let mut collectibles = HashSet::new();
+        for collectible in self.collectibles.iter() {
+            if !collectibles.insert(collectible.id.as_str()) {
+                anyhow::bail!("Collectible \"{}\" appears more than once", collectible.id);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn spawn(&self, args: &mut SpawnArgs) -> LevelPertinentEntities {
         for cube in self.cubes.iter() {
             cube.spawn(args);
@@ -74,13 +246,70 @@ impl SerialLevel {
             plane.spawn(args);
         }
 
+        for box_ in self.boxes.iter() {
+            box_.spawn(args);
+        }
+
         for death_plane in self.death_planes.iter() {
             death_plane.spawn(args);
         }
 
+        for switch in self.switches.iter() {
+            switch.spawn(args);
+        }
+
+        for door in self.doors.iter() {
+            door.spawn(args);
+        }
+
+        for mover in self.movers.iter() {
+            mover.spawn(args);
+        }
+
+        for force_zone in self.force_zones.iter() {
+            force_zone.spawn(args);
+        }
+
+        for light in self.lights.iter() {
+            light.spawn(args);
+        }
+
+        for camera_zone in self.camera_zones.iter() {
+            camera_zone.spawn(args);
+        }
+
+        for key in self.keys.iter() {
+            key.spawn(args);
+        }
+
+        for lock in self.locks.iter() {
+            lock.spawn(args);
+        }
+
+        for extra_life in self.extra_lives.iter() {
+            extra_life.spawn(args);
+        }
+
+        for collectible in self.collectibles.iter() {
+            collectible.spawn(args);
+        }
+
+        for ball_pickup in self.ball_pickups.iter() {
+            ball_pickup.spawn(args);
+        }
+
+        for checkpoint in self.checkpoints.iter() {
+            checkpoint.spawn(args);
+        }
+
+        for goal in self.goals.iter() {
+            goal.spawn(args);
+        }
+
         let spawn = self.spawn.spawn(args);
+        let intro = self.intro.as_ref().map(|intro| intro.spawn(args));
 
-        LevelPertinentEntities { spawn }
+        LevelPertinentEntities { spawn, intro }
     }
 }
 
@@ -92,12 +321,45 @@ pub trait SerialObject {
 pub struct SerialSpawnPoint {
     #[knuffel(child)]
     pos: SerialVec3,
+
+    /// The health the player has in this level
+    #[knuffel(child, unwrap(argument))]
+    health: Option<f32>,
+
+    /// Enables lives mode with this many lives
+    #[knuffel(child, unwrap(argument))]
+    lives: Option<u32>,
+
+    /// Overrides the player config in this level
+    #[knuffel(child, default)]
+    player: SerialPlayerConfig,
+
+    /// Forces the player to use this type of ball
+    #[knuffel(child, unwrap(argument))]
+    ball: Option<SerialBallType>,
+
+    /// Abilities the player can use in this level
+    #[knuffel(children(name = "ability"), unwrap(argument))]
+    abilities: Vec<SerialAbility>,
 }
 
 impl SerialObject for SerialSpawnPoint {
     fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let mut spawn = PlayerSpawnPoint::default();
+        if let Some(health) = self.health {
+            spawn.max_health = health;
+        }
+        spawn.lives = self.lives;
+        spawn.player = self.player;
+        spawn.ball = self.ball.map(Into::into);
+        spawn.abilities = self
+            .abilities
+            .iter()
+            .map(|&ability| ability.into())
+            .collect();
+
         args.commands
-            .spawn(PlayerSpawnPoint)
+            .spawn(spawn)
             .insert(LevelObject)
             .insert(TransformBundle::from_transform(
                 Transform::from_translation(self.pos.into()),
@@ -106,6 +368,65 @@ impl SerialObject for SerialSpawnPoint {
     }
 }
 
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialIntro {
+    /// In seconds
+    #[knuffel(child, unwrap(argument))]
+    duration: f32,
+
+    /// The camera's path, passed through smoothly in order
+    #[knuffel(children(name = "point"))]
+    points: Vec<SerialIntroPoint>,
+}
+
+#[derive(Debug, Copy, Clone, knuffel::Decode)]
+pub struct SerialIntroPoint {
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    /// Where the camera looks from this point
+    #[knuffel(child)]
+    look: SerialVec3,
+}
+
+impl SerialObject for SerialIntro {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        args.commands
+            .spawn(Flyover {
+                points: self
+                    .points
+                    .iter()
+                    .map(|point| FlyoverPoint {
+                        position: point.pos.into(),
+                        look_at: point.look.into(),
+                    })
+                    .collect(),
+                duration: self.duration,
+            })
+            .insert(LevelObject)
+            .id()
+    }
+}
+
+#[derive(Debug, Copy, Clone, knuffel::DecodeScalar)]
+pub enum SerialAbility {
+    Dash,
+    DoubleJump,
+    GroundPound,
+    Brake,
+}
+
+impl From<SerialAbility> for AbilityKind {
+    fn from(value: SerialAbility) -> Self {
+        match value {
+            SerialAbility::Dash => AbilityKind::Dash,
+            SerialAbility::DoubleJump => AbilityKind::DoubleJump,
+            SerialAbility::GroundPound => AbilityKind::GroundPound,
+            SerialAbility::Brake => AbilityKind::Brake,
+        }
+    }
+}
+
 #[derive(Debug, Clone, knuffel::Decode)]
 pub struct SerialDeathPlane {
     #[knuffel(child)]
@@ -146,22 +467,26 @@ pub struct SerialCube {
 
     #[knuffel(argument)]
     size: f32,
+
+    #[knuffel(child)]
+    crumble: Option<SerialCrumble>,
+
+    #[knuffel(child)]
+    hazard: Option<SerialHazard>,
 }
 
 impl SerialObject for SerialCube {
     fn spawn(&self, args: &mut SpawnArgs) -> Entity {
-        let mut rotation = Quat::default();
-        for rot in self.rotations.iter() {
-            rotation = rotation.mul_quat((*rot).into());
-        }
+        let rotation = combine_rotations(&self.rotations);
+        let transform = Transform::from_translation(self.pos.into()).with_rotation(rotation);
 
-        args.commands
-            .spawn(PbrBundle {
-                mesh: args.meshes.add(Mesh::from(shape::Cube { size: self.size })),
-                material: args.materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
-                transform: Transform::from_translation(self.pos.into()).with_rotation(rotation),
-                ..default()
-            })
+        let mut commands = args.commands.spawn(PbrBundle {
+            mesh: args.mesh(Mesh::from(shape::Cube { size: self.size })),
+            material: args.material(Color::rgb(0.8, 0.7, 0.6).into()),
+            transform,
+            ..default()
+        });
+        commands
             .insert(LevelObject)
             .insert(Collider::cuboid(
                 self.size / 2.0,
@@ -169,7 +494,15 @@ impl SerialObject for SerialCube {
                 self.size / 2.0,
             ))
             .insert(RigidBody::Dynamic)
-            .id()
+            .insert(Velocity::default());
+        insert_hazard(&mut commands, &self.hazard);
+
+        if let Some(crumble) = &self.crumble {
+            let entity = commands.id();
+            commands.insert(crumble.to_crumble(entity, transform, RigidBody::Dynamic));
+        }
+
+        commands.id()
     }
 }
 
@@ -186,14 +519,17 @@ pub struct SerialPlane {
 
     #[knuffel(argument)]
     size2: Option<f32>,
+
+    #[knuffel(child)]
+    crumble: Option<SerialCrumble>,
+
+    #[knuffel(child)]
+    hazard: Option<SerialHazard>,
 }
 
 impl SerialObject for SerialPlane {
     fn spawn(&self, args: &mut SpawnArgs) -> Entity {
-        let mut rotation = Quat::default();
-        for rot in self.rotations.iter() {
-            rotation = rotation.mul_quat((*rot).into());
-        }
+        let rotation = combine_rotations(&self.rotations);
 
         let size = if let Some(size2) = self.size2 {
             Vec2::new(self.size, size2)
@@ -201,70 +537,934 @@ impl SerialObject for SerialPlane {
             Vec2::new(self.size, self.size)
         };
 
-        args.commands
-            .spawn(LevelObject)
+        let transform = Transform::from_translation(self.pos.into()).with_rotation(rotation);
+
+        let mut collider = Entity::PLACEHOLDER;
+        let mut commands = args.commands.spawn(LevelObject);
+        commands
             .insert(SpatialBundle {
-                transform: Transform::from_translation(self.pos.into()).with_rotation(rotation),
+                transform,
                 ..Default::default()
             })
+            .insert(RigidBody::Fixed)
             .with_children(|builder| {
                 builder.spawn(PbrBundle {
-                    mesh: args.meshes.add(shape::Quad::new(size).into()),
-                    material: args.materials.add(Color::rgb(0.3, 0.3, 0.3).into()),
+                    mesh: args.mesh(shape::Quad::new(size).into()),
+                    material: args.material(Color::rgb(0.3, 0.3, 0.3).into()),
                     transform: Transform::from_rotation(Quat::from_rotation_x(-PI / 2.0)),
                     ..default()
                 });
-                builder
-                    .spawn(Collider::cuboid(size.x / 2.0, 0.1, size.y / 2.0))
-                    .insert(RigidBody::Fixed)
-                    .insert(TransformBundle::from_transform(Transform::from_xyz(
-                        0.0, -0.1, 0.0,
-                    )));
-            })
-            .id()
+                let mut collider_commands =
+                    builder.spawn(Collider::cuboid(size.x / 2.0, 0.1, size.y / 2.0));
+                collider_commands.insert(TransformBundle::from_transform(Transform::from_xyz(
+                    0.0, -0.1, 0.0,
+                )));
+                insert_hazard(&mut collider_commands, &self.hazard);
+                collider = collider_commands.id();
+            });
+
+        if let Some(crumble) = &self.crumble {
+            commands.insert(crumble.to_crumble(collider, transform, RigidBody::Fixed));
+        }
+
+        commands.id()
     }
 }
 
-#[derive(Debug, Copy, Clone, knuffel::Decode)]
-pub struct SerialRotation {
-    /// Rotation axis
-    #[knuffel(argument)]
-    axis: SerialAxis,
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialBox {
+    #[knuffel(child)]
+    pos: SerialVec3,
 
-    /// Angle in degrees (for human readability)
-    #[knuffel(argument)]
-    angle: f32,
+    #[knuffel(children(name = "rot"))]
+    rotations: Vec<SerialRotation>,
+
+    #[knuffel(child)]
+    size: SerialVec3,
+
+    #[knuffel(child)]
+    color: Option<SerialColor>,
+
+    #[knuffel(child)]
+    crumble: Option<SerialCrumble>,
+
+    #[knuffel(child)]
+    hazard: Option<SerialHazard>,
+
+    /// Degrees per second the box spins around its local Y axis
+    #[knuffel(child, unwrap(argument))]
+    spin: Option<f32>,
+}
+
+impl SerialObject for SerialBox {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let rotation = combine_rotations(&self.rotations);
+        let transform = Transform::from_translation(self.pos.into()).with_rotation(rotation);
+        let size: Vec3 = self.size.into();
+        let color = self
+            .color
+            .map(Into::into)
+            .unwrap_or(Color::rgb(0.3, 0.3, 0.3));
+
+        let mut commands = args.commands.spawn(PbrBundle {
+            mesh: args.mesh(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+            material: args.material(color.into()),
+            transform,
+            ..default()
+        });
+        commands.insert(LevelObject).insert(Collider::cuboid(
+            size.x / 2.0,
+            size.y / 2.0,
+            size.z / 2.0,
+        ));
+        insert_hazard(&mut commands, &self.hazard);
+
+        if let Some(spin) = self.spin {
+            commands
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(Rotator {
+                    speed: spin / 180.0 * PI,
+                });
+        } else {
+            commands.insert(RigidBody::Fixed);
+        }
+
+        if let Some(crumble) = &self.crumble {
+            let entity = commands.id();
+            commands.insert(crumble.to_crumble(entity, transform, RigidBody::Fixed));
+        }
+
+        commands.id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialCrumble {
+    /// Seconds the player can touch the object before it starts shaking
+    #[knuffel(child, unwrap(argument))]
+    delay: Option<f32>,
+
+    /// Contact force that makes the object start shaking immediately
+    #[knuffel(child, unwrap(argument))]
+    force: Option<f32>,
+
+    /// Seconds the object shakes before it crumbles
+    #[knuffel(child, unwrap(argument))]
+    shake: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    mode: Option<SerialCrumbleMode>,
+
+    /// Seconds before the object comes back, if it should come back
+    #[knuffel(child, unwrap(argument))]
+    respawn: Option<f32>,
+}
+
+impl SerialCrumble {
+    fn to_crumble(&self, collider: Entity, origin: Transform, rigid_body: RigidBody) -> Crumble {
+        Crumble {
+            collider,
+            delay: self.delay.unwrap_or(0.5),
+            force_threshold: self.force,
+            shake_time: self.shake.unwrap_or(0.5),
+            mode: self.mode.map(Into::into).unwrap_or_default(),
+            respawn: self.respawn,
+            origin,
+            rigid_body,
+            state: CrumbleState::Intact { contact_time: 0.0 },
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, knuffel::DecodeScalar)]
-pub enum SerialAxis {
-    X,
-    Y,
-    Z,
+pub enum SerialCrumbleMode {
+    Fall,
+    Vanish,
 }
 
-impl From<SerialRotation> for Quat {
-    fn from(value: SerialRotation) -> Self {
-        match value.axis {
-            SerialAxis::X => Quat::from_rotation_x(value.angle / 180.0 * PI),
-            SerialAxis::Y => Quat::from_rotation_y(value.angle / 180.0 * PI),
-            SerialAxis::Z => Quat::from_rotation_z(value.angle / 180.0 * PI),
+impl From<SerialCrumbleMode> for CrumbleMode {
+    fn from(value: SerialCrumbleMode) -> Self {
+        match value {
+            SerialCrumbleMode::Fall => CrumbleMode::Fall,
+            SerialCrumbleMode::Vanish => CrumbleMode::Vanish,
         }
     }
 }
 
 #[derive(Debug, Copy, Clone, knuffel::Decode)]
-pub struct SerialVec3 {
+pub struct SerialHazard {
     #[knuffel(argument)]
-    x: f32,
+    kind: SerialHazardKind,
+
+    /// Damage per hit for `hit` hazards or per second for `burn` hazards
     #[knuffel(argument)]
-    y: f32,
+    amount: Option<f32>,
+}
+
+#[derive(Debug, Copy, Clone, knuffel::DecodeScalar)]
+pub enum SerialHazardKind {
+    Kill,
+    Hit,
+    Burn,
+}
+
+fn insert_hazard(commands: &mut EntityCommands, hazard: &Option<SerialHazard>) {
+    if let Some(hazard) = hazard {
+        let amount = hazard.amount.unwrap_or(1.0);
+        match hazard.kind {
+            SerialHazardKind::Kill => {
+                commands.insert(DeathObject);
+            }
+            SerialHazardKind::Hit => {
+                commands.insert(Hazard {
+                    damage: HazardDamage::Hit(amount),
+                });
+            }
+            SerialHazardKind::Burn => {
+                commands.insert(Hazard {
+                    damage: HazardDamage::Burn(amount),
+                });
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialSwitch {
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    #[knuffel(children(name = "rot"))]
+    rotations: Vec<SerialRotation>,
+
     #[knuffel(argument)]
-    z: f32,
+    size: f32,
+
+    /// Name of the signal emitted while the switch is pressed
+    #[knuffel(child, unwrap(argument))]
+    signal: String,
+
+    #[knuffel(child, unwrap(argument))]
+    activator: Option<SerialSwitchActivator>,
 }
 
-impl From<SerialVec3> for Vec3 {
-    fn from(value: SerialVec3) -> Self {
-        Vec3::new(value.x, value.y, value.z)
+impl SerialObject for SerialSwitch {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let rotation = combine_rotations(&self.rotations);
+
+        args.commands
+            .spawn(Switch {
+                signal: self.signal.clone(),
+                activator: self.activator.map(Into::into).unwrap_or_default(),
+                pressed: false,
+            })
+            .insert(LevelObject)
+            .insert(PbrBundle {
+                mesh: args.mesh(Mesh::from(shape::Box::new(self.size, 0.05, self.size))),
+                material: args.material(Color::rgb(0.8, 0.2, 0.2).into()),
+                transform: Transform::from_translation(self.pos.into()).with_rotation(rotation),
+                ..default()
+            })
+            .insert(Collider::cuboid(self.size / 2.0, 0.1, self.size / 2.0))
+            .insert(Sensor)
+            .insert(RigidBody::Fixed)
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(CollidingEntities::default())
+            .id()
+    }
+}
+
+#[derive(Debug, Copy, Clone, knuffel::DecodeScalar)]
+pub enum SerialSwitchActivator {
+    Player,
+    Cube,
+    Any,
+}
+
+impl From<SerialSwitchActivator> for SwitchActivator {
+    fn from(value: SerialSwitchActivator) -> Self {
+        match value {
+            SerialSwitchActivator::Player => SwitchActivator::Player,
+            SerialSwitchActivator::Cube => SwitchActivator::Cube,
+            SerialSwitchActivator::Any => SwitchActivator::Any,
+        }
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialDoor {
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    #[knuffel(children(name = "rot"))]
+    rotations: Vec<SerialRotation>,
+
+    #[knuffel(child)]
+    size: SerialVec3,
+
+    #[knuffel(children(name = "on"))]
+    listeners: Vec<SerialSignalListener>,
+
+    /// Whether the door starts out open
+    #[knuffel(child, unwrap(argument))]
+    open: Option<bool>,
+}
+
+impl SerialObject for SerialDoor {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let rotation = combine_rotations(&self.rotations);
+        let size: Vec3 = self.size.into();
+
+        args.commands
+            .spawn(Door)
+            .insert(LevelObject)
+            .insert(signal_receiver(&self.listeners, self.open.unwrap_or(false)))
+            .insert(PbrBundle {
+                mesh: args.mesh(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                material: args.material(Color::rgb(0.5, 0.3, 0.2).into()),
+                transform: Transform::from_translation(self.pos.into()).with_rotation(rotation),
+                ..default()
+            })
+            .insert(Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0))
+            .insert(RigidBody::Fixed)
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialMover {
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    /// Position the mover travels to before heading back
+    #[knuffel(child)]
+    to: SerialVec3,
+
+    #[knuffel(children(name = "rot"))]
+    rotations: Vec<SerialRotation>,
+
+    #[knuffel(child)]
+    size: SerialVec3,
+
+    /// Speed in units per second
+    #[knuffel(child, unwrap(argument))]
+    speed: Option<f32>,
+
+    #[knuffel(children(name = "on"))]
+    listeners: Vec<SerialSignalListener>,
+
+    #[knuffel(child, unwrap(argument))]
+    enabled: Option<bool>,
+}
+
+impl SerialObject for SerialMover {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let rotation = combine_rotations(&self.rotations);
+        let size: Vec3 = self.size.into();
+
+        args.commands
+            .spawn(Mover {
+                start: self.pos.into(),
+                end: self.to.into(),
+                speed: self.speed.unwrap_or(1.0),
+                progress: 0.0,
+                forward: true,
+            })
+            .insert(LevelObject)
+            .insert(signal_receiver(
+                &self.listeners,
+                self.enabled.unwrap_or(true),
+            ))
+            .insert(PbrBundle {
+                mesh: args.mesh(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                material: args.material(Color::rgb(0.6, 0.6, 0.7).into()),
+                transform: Transform::from_translation(self.pos.into()).with_rotation(rotation),
+                ..default()
+            })
+            .insert(Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0))
+            .insert(RigidBody::KinematicPositionBased)
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialForceZone {
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    #[knuffel(child)]
+    size: SerialVec3,
+
+    /// Acceleration applied to bodies inside the zone
+    #[knuffel(child)]
+    acceleration: SerialVec3,
+
+    #[knuffel(children(name = "on"))]
+    listeners: Vec<SerialSignalListener>,
+
+    #[knuffel(child, unwrap(argument))]
+    enabled: Option<bool>,
+}
+
+impl SerialObject for SerialForceZone {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let size: Vec3 = self.size.into();
+
+        args.commands
+            .spawn(ForceZone {
+                acceleration: self.acceleration.into(),
+            })
+            .insert(LevelObject)
+            .insert(signal_receiver(
+                &self.listeners,
+                self.enabled.unwrap_or(true),
+            ))
+            .insert(TransformBundle::from_transform(
+                Transform::from_translation(self.pos.into()),
+            ))
+            .insert(Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0))
+            .insert(Sensor)
+            .insert(RigidBody::Fixed)
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(CollidingEntities::default())
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialCameraZone {
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    #[knuffel(child)]
+    size: SerialVec3,
+
+    /// Keeps the camera at this position while the player is inside
+    #[knuffel(child)]
+    fixed: Option<SerialVec3>,
+
+    /// Slides the camera along this path while the player is inside
+    #[knuffel(child)]
+    rail: Option<SerialRail>,
+
+    /// Locks the direction the camera faces, in degrees around the Y axis. 0 faces along -Z
+    #[knuffel(child, unwrap(argument))]
+    yaw: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    distance: Option<f32>,
+
+    /// The lowest pitch the camera can have, in degrees. Negative pitches look down on the ball
+    #[knuffel(child, unwrap(argument))]
+    pitch_min: Option<f32>,
+
+    /// The highest pitch the camera can have, in degrees
+    #[knuffel(child, unwrap(argument))]
+    pitch_max: Option<f32>,
+
+    /// Seconds the camera takes to move over when entering or leaving the zone
+    #[knuffel(child, unwrap(argument))]
+    blend: Option<f32>,
+
+    /// Where zones overlap the one with the highest priority is used
+    #[knuffel(child, unwrap(argument))]
+    priority: Option<i32>,
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialRail {
+    #[knuffel(children(name = "point"))]
+    points: Vec<SerialVec3>,
+}
+
+impl SerialObject for SerialCameraZone {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let size: Vec3 = self.size.into();
+        let placement = if let Some(fixed) = self.fixed {
+            CameraPlacement::Fixed(fixed.into())
+        } else if let Some(rail) = &self.rail {
+            CameraPlacement::Rail(rail.points.iter().map(|&point| point.into()).collect())
+        } else {
+            CameraPlacement::Orbit
+        };
+
+        args.commands
+            .spawn(CameraZone {
+                placement,
+                yaw: self.yaw.map(f32::to_radians),
+                distance: self.distance,
+                min_pitch: self.pitch_min.map(f32::to_radians),
+                max_pitch: self.pitch_max.map(f32::to_radians),
+                blend_time: self.blend.unwrap_or(0.5),
+                priority: self.priority.unwrap_or(0),
+            })
+            .insert(LevelObject)
+            .insert(TransformBundle::from_transform(
+                Transform::from_translation(self.pos.into()),
+            ))
+            .insert(Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0))
+            .insert(Sensor)
+            .insert(RigidBody::Fixed)
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(CollidingEntities::default())
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialLight {
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    #[knuffel(child)]
+    color: Option<SerialColor>,
+
+    #[knuffel(child, unwrap(argument))]
+    intensity: Option<f32>,
+
+    #[knuffel(children(name = "on"))]
+    listeners: Vec<SerialSignalListener>,
+
+    #[knuffel(child, unwrap(argument))]
+    enabled: Option<bool>,
+}
+
+impl SerialObject for SerialLight {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let intensity = self.intensity.unwrap_or(800.0);
+        let receiver = signal_receiver(&self.listeners, self.enabled.unwrap_or(true));
+
+        args.commands
+            .spawn(LevelLight { intensity })
+            .insert(LevelObject)
+            .insert(PointLightBundle {
+                point_light: PointLight {
+                    intensity: if receiver.enabled { intensity } else { 0.0 },
+                    color: self.color.map(Into::into).unwrap_or(Color::WHITE),
+                    shadows_enabled: true,
+                    ..default()
+                },
+                transform: Transform::from_translation(self.pos.into()),
+                ..default()
+            })
+            .insert(receiver)
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialKey {
+    /// The id locks use to refer to this key
+    #[knuffel(argument)]
+    id: String,
+
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    #[knuffel(child)]
+    color: Option<SerialColor>,
+}
+
+impl SerialObject for SerialKey {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let color = self.color.map(Into::into).unwrap_or(Color::YELLOW);
+
+        args.commands
+            .spawn(Pickup {
+                kind: PickupKind::Key {
+                    id: self.id.clone(),
+                    color,
+                },
+            })
+            .insert(LevelObject)
+            .insert(Spin)
+            .insert(PbrBundle {
+                mesh: args.mesh(Mesh::from(shape::Box::new(0.2, 0.4, 0.1))),
+                material: args.material(StandardMaterial {
+                    base_color: color,
+                    emissive: color,
+                    ..default()
+                }),
+                transform: Transform::from_translation(self.pos.into()),
+                ..default()
+            })
+            .insert(Collider::ball(0.3))
+            .insert(Sensor)
+            .insert(RigidBody::Fixed)
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialLock {
+    /// The id of the key that opens this lock
+    #[knuffel(argument)]
+    key: String,
+
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    #[knuffel(children(name = "rot"))]
+    rotations: Vec<SerialRotation>,
+
+    #[knuffel(child)]
+    size: SerialVec3,
+
+    #[knuffel(child)]
+    color: Option<SerialColor>,
+}
+
+impl SerialObject for SerialLock {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let rotation = combine_rotations(&self.rotations);
+        let size: Vec3 = self.size.into();
+        let color = self.color.map(Into::into).unwrap_or(Color::YELLOW);
+
+        args.commands
+            .spawn(Lock {
+                key: self.key.clone(),
+            })
+            .insert(LevelObject)
+            .insert(PbrBundle {
+                mesh: args.mesh(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                material: args.material(StandardMaterial {
+                    base_color: color.with_a(0.6),
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                }),
+                transform: Transform::from_translation(self.pos.into()).with_rotation(rotation),
+                ..default()
+            })
+            .insert(Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0))
+            .insert(RigidBody::Fixed)
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialExtraLife {
+    #[knuffel(child)]
+    pos: SerialVec3,
+}
+
+impl SerialObject for SerialExtraLife {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        args.commands
+            .spawn(Pickup {
+                kind: PickupKind::ExtraLife,
+            })
+            .insert(LevelObject)
+            .insert(Spin)
+            .insert(PbrBundle {
+                mesh: args.mesh(
+                    shape::UVSphere {
+                        radius: 0.2,
+                        sectors: 16,
+                        stacks: 16,
+                    }
+                    .into(),
+                ),
+                material: args.material(StandardMaterial {
+                    base_color: Color::GREEN,
+                    emissive: Color::GREEN,
+                    ..default()
+                }),
+                transform: Transform::from_translation(self.pos.into()),
+                ..default()
+            })
+            .insert(Collider::ball(0.3))
+            .insert(Sensor)
+            .insert(RigidBody::Fixed)
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialCollectible {
+    /// The id the save profile remembers this collectible by
+    #[knuffel(argument)]
+    id: String,
+
+    #[knuffel(child)]
+    pos: SerialVec3,
+}
+
+impl SerialObject for SerialCollectible {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        args.commands
+            .spawn(Pickup {
+                kind: PickupKind::Collectible {
+                    id: self.id.clone(),
+                },
+            })
+            .insert(LevelObject)
+            .insert(Spin)
+            .insert(PbrBundle {
+                mesh: args.mesh(Mesh::from(shape::Cube { size: 0.3 })),
+                material: args.material(StandardMaterial {
+                    base_color: Color::rgb(0.8, 0.3, 1.0),
+                    emissive: Color::rgb(1.6, 0.6, 2.0),
+                    ..default()
+                }),
+                transform: Transform::from_translation(self.pos.into()).with_rotation(
+                    Quat::from_rotation_x(PI / 4.0) * Quat::from_rotation_z(PI / 4.0),
+                ),
+                ..default()
+            })
+            .insert(Collider::ball(0.3))
+            .insert(Sensor)
+            .insert(RigidBody::Fixed)
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialBallPickup {
+    /// The type of ball the player turns into
+    #[knuffel(argument)]
+    ball: SerialBallType,
+
+    #[knuffel(child)]
+    pos: SerialVec3,
+}
+
+impl SerialObject for SerialBallPickup {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let ball = BallType::from(self.ball);
+
+        args.commands
+            .spawn(Pickup {
+                kind: PickupKind::Ball(ball),
+            })
+            .insert(LevelObject)
+            .insert(Spin)
+            .insert(PbrBundle {
+                mesh: args.mesh(
+                    shape::UVSphere {
+                        radius: 0.25,
+                        sectors: 16,
+                        stacks: 16,
+                    }
+                    .into(),
+                ),
+                material: args.material(StandardMaterial {
+                    emissive: ball.properties().light_color,
+                    ..ball.properties().material()
+                }),
+                transform: Transform::from_translation(self.pos.into()),
+                ..default()
+            })
+            .insert(Collider::ball(0.3))
+            .insert(Sensor)
+            .insert(RigidBody::Fixed)
+            .id()
+    }
+}
+
+#[derive(Debug, Copy, Clone, knuffel::DecodeScalar)]
+pub enum SerialBallType {
+    Normal,
+    Steel,
+    Paper,
+    Rubber,
+    Sticky,
+}
+
+impl From<SerialBallType> for BallType {
+    fn from(value: SerialBallType) -> Self {
+        match value {
+            SerialBallType::Normal => BallType::Normal,
+            SerialBallType::Steel => BallType::Steel,
+            SerialBallType::Paper => BallType::Paper,
+            SerialBallType::Rubber => BallType::Rubber,
+            SerialBallType::Sticky => BallType::Sticky,
+        }
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialCheckpoint {
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    /// Where the player respawns after reaching this checkpoint. Defaults to 1 unit above it
+    #[knuffel(child)]
+    respawn: Option<SerialVec3>,
+
+    #[knuffel(argument)]
+    size: f32,
+}
+
+impl SerialObject for SerialCheckpoint {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        let pos: Vec3 = self.pos.into();
+
+        args.commands
+            .spawn(Checkpoint {
+                reached: false,
+                respawn: self.respawn.map_or(pos + Vec3::Y, Into::into),
+            })
+            .insert(LevelObject)
+            .insert(PbrBundle {
+                mesh: args.mesh(Mesh::from(shape::Box::new(self.size, 0.02, self.size))),
+                material: args.material(StandardMaterial {
+                    base_color: Color::rgb(0.2, 0.5, 1.0),
+                    emissive: Color::rgb(0.2, 0.5, 1.0),
+                    ..default()
+                }),
+                transform: Transform::from_translation(self.pos.into()),
+                ..default()
+            })
+            .insert(Collider::cuboid(
+                self.size / 2.0,
+                self.size / 2.0,
+                self.size / 2.0,
+            ))
+            .insert(Sensor)
+            .insert(RigidBody::Fixed)
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialGoal {
+    #[knuffel(child)]
+    pos: SerialVec3,
+
+    #[knuffel(argument)]
+    size: f32,
+}
+
+impl SerialObject for SerialGoal {
+    fn spawn(&self, args: &mut SpawnArgs) -> Entity {
+        args.commands
+            .spawn(Goal::default())
+            .insert(LevelObject)
+            .insert(PbrBundle {
+                mesh: args.mesh(Mesh::from(shape::Box::new(self.size, 0.02, self.size))),
+                material: args.material(StandardMaterial {
+                    base_color: Color::rgb(1.0, 0.8, 0.2),
+                    emissive: Color::rgb(4.0, 3.2, 0.8),
+                    ..default()
+                }),
+                transform: Transform::from_translation(self.pos.into()),
+                ..default()
+            })
+            .insert(Collider::cuboid(
+                self.size / 2.0,
+                self.size / 2.0,
+                self.size / 2.0,
+            ))
+            .insert(Sensor)
+            .insert(RigidBody::Fixed)
+            .id()
+    }
+}
+
+#[derive(Debug, Clone, knuffel::Decode)]
+pub struct SerialSignalListener {
+    /// Name of the signal to listen to
+    #[knuffel(argument)]
+    signal: String,
+
+    #[knuffel(argument)]
+    action: SerialSignalAction,
+}
+
+#[derive(Debug, Copy, Clone, knuffel::DecodeScalar)]
+pub enum SerialSignalAction {
+    Enable,
+    Disable,
+    Toggle,
+}
+
+impl From<SerialSignalAction> for SignalAction {
+    fn from(value: SerialSignalAction) -> Self {
+        match value {
+            SerialSignalAction::Enable => SignalAction::Enable,
+            SerialSignalAction::Disable => SignalAction::Disable,
+            SerialSignalAction::Toggle => SignalAction::Toggle,
+        }
+    }
+}
+
+fn signal_receiver(listeners: &[SerialSignalListener], enabled: bool) -> SignalReceiver {
+    SignalReceiver::new(
+        listeners
+            .iter()
+            .map(|listener| SignalListener {
+                signal: listener.signal.clone(),
+                action: listener.action.into(),
+            })
+            .collect(),
+        enabled,
+    )
+}
+
+#[derive(Debug, Copy, Clone, knuffel::Decode)]
+pub struct SerialRotation {
+    /// Rotation axis
+    #[knuffel(argument)]
+    axis: SerialAxis,
+
+    /// Angle in degrees (for human readability)
+    #[knuffel(argument)]
+    angle: f32,
+}
+
+#[derive(Debug, Copy, Clone, knuffel::DecodeScalar)]
+pub enum SerialAxis {
+    X,
+    Y,
+    Z,
+}
+
+fn combine_rotations(rotations: &[SerialRotation]) -> Quat {
+    let mut rotation = Quat::default();
+    for rot in rotations.iter() {
+        rotation = rotation.mul_quat((*rot).into());
+    }
+    rotation
+}
+
+impl From<SerialRotation> for Quat {
+    fn from(value: SerialRotation) -> Self {
+        match value.axis {
+            SerialAxis::X => Quat::from_rotation_x(value.angle / 180.0 * PI),
+            SerialAxis::Y => Quat::from_rotation_y(value.angle / 180.0 * PI),
+            SerialAxis::Z => Quat::from_rotation_z(value.angle / 180.0 * PI),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, knuffel::Decode)]
+pub struct SerialVec3 {
+    #[knuffel(argument)]
+    x: f32,
+    #[knuffel(argument)]
+    y: f32,
+    #[knuffel(argument)]
+    z: f32,
+}
+
+impl From<SerialVec3> for Vec3 {
+    fn from(value: SerialVec3) -> Self {
+        Vec3::new(value.x, value.y, value.z)
+    }
+}
+
+#[derive(Debug, Copy, Clone, knuffel::Decode)]
+pub struct SerialColor {
+    #[knuffel(argument)]
+    r: f32,
+    #[knuffel(argument)]
+    g: f32,
+    #[knuffel(argument)]
+    b: f32,
+}
+
+impl From<SerialColor> for Color {
+    fn from(value: SerialColor) -> Self {
+        Color::rgb(value.r, value.g, value.b)
     }
 }
@@ -0,0 +1,85 @@
+use crate::level::logic::pickup::{PickupEvent, PickupKind};
+use crate::level::logic::PlayerDiedEvent;
+use crate::level::{LevelLoadedEvent, PlayerSpawnPoint, RestartLevelEvent};
+use crate::AppState;
+use bevy::prelude::*;
+
+pub struct LivesPlugin;
+
+impl Plugin for LivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lives>()
+            .add_systems(Update, reset_lives)
+            .add_systems(
+                Update,
+                (collect_extra_lives, lose_lives)
+                    .chain()
+                    .after(reset_lives)
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// The player's remaining lives in the current level, if the level uses lives. Lives are kept when
+/// the level is restarted and only start over when it is loaded fresh or retried after a game
+/// over.
+#[derive(Default, Debug, Copy, Clone, Resource)]
+pub struct Lives {
+    pub remaining: Option<u32>,
+}
+
+fn reset_lives(
+    mut lives: ResMut<Lives>,
+    mut restart_events: EventReader<RestartLevelEvent>,
+    mut level_load: EventReader<LevelLoadedEvent>,
+    spawnpoint: Query<&PlayerSpawnPoint>,
+    mut keep: Local<bool>,
+) {
+    // otherwise restarting would be a way to get lives back
+    if restart_events.read().next().is_some() {
+        *keep = lives.remaining != Some(0);
+    }
+
+    if let Some(level) = level_load.read().next() {
+        if !std::mem::take(&mut *keep) {
+            lives.remaining = spawnpoint
+                .get(level.entities.spawn)
+                .ok()
+                .and_then(|spawn| spawn.lives);
+        }
+    }
+
+    restart_events.clear();
+    level_load.clear();
+}
+
+fn collect_extra_lives(mut lives: ResMut<Lives>, mut events: EventReader<PickupEvent>) {
+    for event in events.read() {
+        if event.kind == PickupKind::ExtraLife {
+            if let Some(remaining) = &mut lives.remaining {
+                *remaining += 1;
+            }
+        }
+    }
+}
+
+fn lose_lives(
+    mut lives: ResMut<Lives>,
+    mut died_events: EventReader<PlayerDiedEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for _ in died_events.read() {
+        if let Some(remaining) = &mut lives.remaining {
+            *remaining = remaining.saturating_sub(1);
+
+            if *remaining == 0 {
+                info!("Game over.");
+
+                next_state.set(AppState::GameOver);
+                break;
+            }
+        }
+    }
+
+    died_events.clear();
+}
@@ -1,25 +1,68 @@
+mod actions;
+mod ghost;
+mod hud;
+mod intro;
 mod level;
+mod lives;
 mod menu;
+mod physics;
 mod player;
+mod replay;
+mod save;
+mod settings;
+#[cfg(test)]
+mod tests;
+mod timer;
 mod util;
 
-use crate::level::{LevelLoadedEvent, LevelsPlugin};
+use crate::actions::{Action, ActionState, ActionsPlugin};
+use crate::ghost::GhostPlugin;
+use crate::hud::HudPlugin;
+use crate::intro::{start_intro, Intro, IntroPlugin};
+use crate::level::{LevelLoadedEvent, LevelsPlugin, RestartLevelEvent};
+use crate::lives::LivesPlugin;
 use crate::menu::MenuPlugin;
-use crate::player::PlayerPlugin;
+use crate::physics::PhysicsPlugin;
+use crate::player::config::PlayerConfigPlugin;
+use crate::player::{PlayerPlugin, PlayerVisualsPlugin};
+use crate::replay::ReplayPlugin;
+use crate::save::SavePlugin;
+use crate::settings::SettingsPlugin;
+use crate::timer::TimerPlugin;
 use bevy::prelude::*;
 use bevy::window::CursorGrabMode;
-use bevy_rapier3d::prelude::*;
+
+/// Rate of the fixed update schedule that gameplay is measured in.
+pub const PHYSICS_TICK_RATE: f64 = 60.0;
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+        .add_plugins(ActionsPlugin)
+        .add_plugins(PhysicsPlugin::default())
         .add_state::<AppState>()
+        .add_plugins(SavePlugin)
+        .add_plugins(SettingsPlugin)
         .add_plugins(LevelsPlugin)
         .add_plugins(PlayerPlugin)
+        .add_plugins(PlayerConfigPlugin)
+        .add_plugins(PlayerVisualsPlugin)
+        .add_plugins(LivesPlugin)
+        .add_plugins(TimerPlugin)
+        .add_plugins(ReplayPlugin)
+        .add_plugins(GhostPlugin)
+        .add_plugins(IntroPlugin)
         .add_plugins(MenuPlugin)
-        .add_systems(Startup, setup_physics)
-        .add_systems(Update, (pause_game, state_respond, set_in_game))
+        .add_plugins(HudPlugin)
+        .add_systems(
+            Update,
+            (
+                pause_game,
+                reset_level,
+                state_respond,
+                set_in_game.after(start_intro),
+            ),
+        )
         .run();
 }
 
@@ -27,31 +70,28 @@ fn main() {
 enum AppState {
     #[default]
     MainMenu,
+    ProfileMenu,
+    SettingsMenu,
+    ControlsMenu,
     Loading,
+    /// The level's flyover is playing before the player gets control.
+    Intro,
     PauseMenu,
     InGame,
+    GameOver,
+    LevelComplete,
 }
 
-fn setup_physics(mut physics: ResMut<RapierConfiguration>) {
-    physics.physics_pipeline_active = false;
-}
-
-fn state_respond(
-    mut windows: Query<&mut Window>,
-    cur_state: Res<State<AppState>>,
-    mut physics: ResMut<RapierConfiguration>,
-) {
+fn state_respond(mut windows: Query<&mut Window>, cur_state: Res<State<AppState>>) {
     let mut window = windows.single_mut();
 
     if cur_state.is_changed() {
         if *cur_state.get() == AppState::InGame {
             window.cursor.visible = false;
             window.cursor.grab_mode = CursorGrabMode::Locked;
-            physics.physics_pipeline_active = true;
         } else {
             window.cursor.visible = true;
             window.cursor.grab_mode = CursorGrabMode::None;
-            physics.physics_pipeline_active = false;
         }
     }
 }
@@ -59,9 +99,9 @@ fn state_respond(
 fn pause_game(
     cur_state: Res<State<AppState>>,
     mut next_state: ResMut<NextState<AppState>>,
-    key: Res<Input<KeyCode>>,
+    actions: Res<ActionState>,
 ) {
-    if key.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::Pause) {
         if *cur_state.get() == AppState::InGame {
             next_state.set(AppState::PauseMenu);
         } else if *cur_state.get() == AppState::PauseMenu {
@@ -70,14 +110,31 @@ fn pause_game(
     }
 }
 
+fn reset_level(
+    cur_state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut restart_events: EventWriter<RestartLevelEvent>,
+    actions: Res<ActionState>,
+) {
+    if *cur_state.get() == AppState::InGame && actions.just_pressed(Action::Reset) {
+        next_state.set(AppState::Loading);
+        restart_events.send(RestartLevelEvent);
+    }
+}
+
 fn set_in_game(
     cur_state: Res<State<AppState>>,
     mut next_state: ResMut<NextState<AppState>>,
+    intro: Res<Intro>,
     mut level_load: EventReader<LevelLoadedEvent>,
 ) {
     if *cur_state.get() == AppState::Loading {
-        if let Some(_) = level_load.read().next() {
-            next_state.set(AppState::InGame);
+        if level_load.read().next().is_some() {
+            if intro.is_playing() {
+                next_state.set(AppState::Intro);
+            } else {
+                next_state.set(AppState::InGame);
+            }
         }
     }
 
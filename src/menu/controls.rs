@@ -0,0 +1,292 @@
+//! The screen for rebinding controls, reachable from the settings screen.
+
+use super::focus::CancelButton;
+use super::spawn_button;
+use crate::actions::{Action, Binding, Bindings};
+use crate::save::Profile;
+use crate::AppState;
+use bevy::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.init_resource::<Rebinding>()
+        .add_systems(Update, manage_controls_menu)
+        .add_systems(OnExit(AppState::ControlsMenu), stop_rebinding)
+        .add_systems(
+            Update,
+            capture_binding
+                .before(binding_listener)
+                .run_if(in_state(AppState::ControlsMenu)),
+        )
+        .add_systems(
+            Update,
+            (
+                controls_menu_listener,
+                binding_listener,
+                reset_bindings_listener,
+                controls_back_listener,
+            ),
+        );
+}
+
+/// How many bindings each action can have.
+const SLOTS: usize = 3;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct ControlsMenu;
+
+/// Opens the controls screen.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct ControlsMenuButton;
+
+/// Waits for a new key for one of an action's bindings when pressed.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct BindingButton {
+    pub action: Action,
+    pub slot: usize,
+}
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct ResetBindingsButton;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct ControlsBackButton;
+
+/// The binding waiting for a key or button, if any.
+#[derive(Default, Debug, Copy, Clone, Resource)]
+pub struct Rebinding {
+    pub target: Option<(Action, usize)>,
+}
+
+fn manage_controls_menu(
+    app_state: Res<State<AppState>>,
+    controls_menu_query: Query<Entity, With<ControlsMenu>>,
+    profile: Res<Profile>,
+    rebinding: Res<Rebinding>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    if app_state.is_changed() || profile.is_changed() || rebinding.is_changed() {
+        if let Some(menu) = controls_menu_query.iter().next() {
+            commands.entity(menu).despawn_recursive();
+        }
+
+        if *app_state.get() == AppState::ControlsMenu {
+            let bindings = &profile.settings.bindings;
+            let text_style = TextStyle {
+                font: assets.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 32.0,
+                color: Color::rgb(0.9, 0.9, 0.9),
+                ..default()
+            };
+
+            commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(ControlsMenu)
+                .with_children(|parent| {
+                    for action in Action::ALL {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Row,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(
+                                    TextBundle::from_section(action.name(), text_style.clone())
+                                        .with_style(Style {
+                                            width: Val::Px(200.0),
+                                            ..default()
+                                        }),
+                                );
+
+                                for slot in 0..SLOTS {
+                                    let label = if rebinding.target == Some((action, slot)) {
+                                        "Press a key...".to_string()
+                                    } else {
+                                        binding_label(bindings, action, slot)
+                                    };
+
+                                    spawn_button(
+                                        parent,
+                                        Val::Px(260.0), Val::Px(50.0),
+                                        label,
+                                        &assets,
+                                    )
+                                    .insert(BindingButton { action, slot });
+                                }
+                            });
+                    }
+
+                    let message = if rebinding.target.is_some() {
+                        "Escape cancels, Backspace clears the binding.".to_string()
+                    } else {
+                        conflict_message(bindings)
+                    };
+                    parent.spawn(TextBundle::from_section(
+                        message,
+                        TextStyle {
+                            color: Color::rgb(0.9, 0.2, 0.2),
+                            ..text_style
+                        },
+                    ));
+
+                    spawn_button(
+                        parent,
+                        Val::Px(300.0), Val::Px(65.0),
+                        "Reset Controls",
+                        &assets,
+                    )
+                    .insert(ResetBindingsButton);
+                    spawn_button(
+                        parent,
+                        Val::Px(300.0), Val::Px(65.0),
+                        "Back",
+                        &assets,
+                    )
+                    .insert(ControlsBackButton)
+                    .insert(CancelButton);
+                });
+        }
+    }
+}
+
+/// The name of a binding, marked if it also triggers another action.
+fn binding_label(bindings: &Bindings, action: Action, slot: usize) -> String {
+    match bindings.get(action).get(slot) {
+        Some(&binding) if bindings.is_conflicting(binding) => format!("{} (!)", binding.name()),
+        Some(binding) => binding.name(),
+        None => "-".to_string(),
+    }
+}
+
+/// Describes every key that is bound to more than one action.
+fn conflict_message(bindings: &Bindings) -> String {
+    bindings
+        .conflicts()
+        .iter()
+        .map(|(binding, actions)| {
+            let actions: Vec<_> = actions.iter().map(|action| action.name()).collect();
+            format!("{} is bound to {}", binding.name(), actions.join(" and "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Binds the next key or button pressed to the binding waiting for one.
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    mut profile: ResMut<Profile>,
+    key: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+) {
+    let Some((action, slot)) = rebinding.target else {
+        return;
+    };
+
+    if key.just_pressed(KeyCode::Escape) {
+        rebinding.target = None;
+        return;
+    }
+
+    let binding = if key.just_pressed(KeyCode::Back) {
+        None
+    } else if let Some(&code) = key.get_just_pressed().next() {
+        Some(Binding::Key(code))
+    } else if let Some(&button) = mouse.get_just_pressed().next() {
+        Some(Binding::Mouse(button))
+    } else if let Some(button) = gamepad_buttons.get_just_pressed().next() {
+        Some(Binding::Gamepad(button.button_type))
+    } else {
+        return;
+    };
+
+    let bound = profile.settings.bindings.get_mut(action);
+    match binding {
+        Some(binding) if slot < bound.len() => bound[slot] = binding,
+        Some(binding) => bound.push(binding),
+        None if slot < bound.len() => {
+            bound.remove(slot);
+        }
+        None => {}
+    }
+    // the same key twice on one action is pointless
+    bound.dedup();
+
+    if let Err(err) = profile.save() {
+        error!("Error saving profile: {}", err);
+    }
+    rebinding.target = None;
+}
+
+pub fn is_rebinding(rebinding: Res<Rebinding>) -> bool {
+    rebinding.target.is_some()
+}
+
+fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.target = None;
+}
+
+fn controls_menu_listener(
+    controls_menu: Query<&Interaction, (Changed<Interaction>, With<ControlsMenuButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for &interaction in controls_menu.iter() {
+        if interaction == Interaction::Pressed {
+            app_state.set(AppState::ControlsMenu);
+            return;
+        }
+    }
+}
+
+fn binding_listener(
+    binding_buttons: Query<(&Interaction, &BindingButton), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    for (&interaction, button) in binding_buttons.iter() {
+        if interaction == Interaction::Pressed {
+            rebinding.target = Some((button.action, button.slot));
+            return;
+        }
+    }
+}
+
+fn reset_bindings_listener(
+    reset: Query<&Interaction, (Changed<Interaction>, With<ResetBindingsButton>)>,
+    mut profile: ResMut<Profile>,
+) {
+    for &interaction in reset.iter() {
+        if interaction == Interaction::Pressed {
+            profile.settings.bindings = Bindings::default();
+            if let Err(err) = profile.save() {
+                error!("Error saving profile: {}", err);
+            }
+            return;
+        }
+    }
+}
+
+fn controls_back_listener(
+    back: Query<&Interaction, (Changed<Interaction>, With<ControlsBackButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for &interaction in back.iter() {
+        if interaction == Interaction::Pressed {
+            app_state.set(AppState::SettingsMenu);
+            return;
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Moving between menu buttons and pressing them without a mouse.
+
+use super::controls::is_rebinding;
+use super::profile::is_renaming;
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+
+pub fn setup(app: &mut App) {
+    app.init_resource::<MenuFocus>()
+        .init_resource::<MenuInput>()
+        .add_systems(
+            PreUpdate,
+            (
+                read_gamepad_menu_input,
+                read_keyboard_menu_input,
+                move_focus,
+                press_focused,
+            )
+                .chain()
+                .after(UiSystem::Focus)
+                // those screens want the keys for themselves
+                .run_if(not(is_rebinding))
+                .run_if(not(is_renaming)),
+        )
+        .add_systems(Update, outline_focused);
+}
+
+/// How far a stick has to be pushed to move the focus.
+const STICK_THRESHOLD: f32 = 0.5;
+/// How long a stick has to be held before the focus moves again, in seconds.
+const STICK_REPEAT: f32 = 0.25;
+
+const FOCUS_OUTLINE: Color = Color::rgb(0.9, 0.8, 0.3);
+
+/// Pressed when the player wants to go back, like the "Back" button of a menu.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct CancelButton;
+
+/// The button that is pressed by confirming instead of clicking.
+#[derive(Default, Debug, Copy, Clone, Resource)]
+pub struct MenuFocus {
+    pub entity: Option<Entity>,
+    /// Where the focused button was, so the closest button can take over when a menu is rebuilt.
+    position: Vec2,
+}
+
+/// What the player asked the menu to do this frame.
+#[derive(Default, Debug, Copy, Clone, Resource)]
+pub struct MenuInput {
+    /// The direction to move the focus in, with y pointing down like the UI.
+    pub direction: Option<Vec2>,
+    /// Moves the focus through the buttons in reading order, backwards if negative.
+    pub step: i32,
+    pub confirm: bool,
+    pub cancel: bool,
+}
+
+fn read_gamepad_menu_input(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time>,
+    mut stick_repeat: Local<f32>,
+    mut input: ResMut<MenuInput>,
+) {
+    let mut new_input = MenuInput::default();
+    let mut stick = Vec2::ZERO;
+
+    for gamepad in gamepads.iter() {
+        let just_pressed =
+            |button_type| buttons.just_pressed(GamepadButton::new(gamepad, button_type));
+        let axis = |axis_type| {
+            axes.get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or(0.0)
+        };
+
+        for (button_type, direction) in [
+            (GamepadButtonType::DPadUp, Vec2::NEG_Y),
+            (GamepadButtonType::DPadDown, Vec2::Y),
+            (GamepadButtonType::DPadLeft, Vec2::NEG_X),
+            (GamepadButtonType::DPadRight, Vec2::X),
+        ] {
+            if just_pressed(button_type) {
+                new_input.direction = Some(direction);
+            }
+        }
+
+        new_input.confirm |= just_pressed(GamepadButtonType::South);
+        new_input.cancel |= just_pressed(GamepadButtonType::East);
+
+        stick += Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            -axis(GamepadAxisType::LeftStickY),
+        );
+    }
+
+    // holding the stick keeps moving the focus, a little slower than the menu could be read
+    if stick.length() > STICK_THRESHOLD {
+        *stick_repeat -= time.delta_seconds();
+        if *stick_repeat <= 0.0 {
+            *stick_repeat = STICK_REPEAT;
+            new_input.direction = new_input.direction.or(Some(stick.normalize()));
+        }
+    } else {
+        *stick_repeat = 0.0;
+    }
+
+    *input = new_input;
+}
+
+/// Arrow keys move the focus, tab steps through the buttons in order.
+fn read_keyboard_menu_input(key: Res<Input<KeyCode>>, mut input: ResMut<MenuInput>) {
+    for (code, direction) in [
+        (KeyCode::Up, Vec2::NEG_Y),
+        (KeyCode::Down, Vec2::Y),
+        (KeyCode::Left, Vec2::NEG_X),
+        (KeyCode::Right, Vec2::X),
+    ] {
+        if key.just_pressed(code) {
+            input.direction = Some(direction);
+        }
+    }
+
+    if key.just_pressed(KeyCode::Tab) {
+        input.step = if key.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            -1
+        } else {
+            1
+        };
+    }
+
+    input.confirm |= key.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]);
+    input.cancel |= key.just_pressed(KeyCode::Escape);
+}
+
+fn move_focus(
+    input: Res<MenuInput>,
+    mut focus: ResMut<MenuFocus>,
+    buttons: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Button>>,
+) {
+    let buttons: Vec<(Entity, Vec2)> = buttons
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(entity, transform, _)| (entity, transform.translation().truncate()))
+        .collect();
+
+    let current = focus
+        .entity
+        .and_then(|focused| buttons.iter().find(|(entity, _)| *entity == focused))
+        .copied();
+
+    let wants_focus = input.direction.is_some() || input.step != 0;
+    let next = match (current, input.direction) {
+        (Some((_, position)), Some(direction)) => {
+            closest_in_direction(&buttons, position, direction).or(current)
+        }
+        (Some(current), None) if input.step != 0 => step_in_order(&buttons, current, input.step),
+        (Some(current), None) => Some(current),
+        // the focused button went away with its menu, or nothing was focused yet
+        (None, _) if focus.entity.is_some() || wants_focus => closest(&buttons, focus.position),
+        (None, _) => None,
+    };
+
+    let entity = next.map(|(entity, _)| entity);
+    if focus.entity != entity {
+        focus.entity = entity;
+    }
+    if let Some((_, position)) = next {
+        if focus.position != position {
+            focus.position = position;
+        }
+    }
+}
+
+fn closest(buttons: &[(Entity, Vec2)], position: Vec2) -> Option<(Entity, Vec2)> {
+    buttons
+        .iter()
+        .min_by(|(_, a), (_, b)| {
+            a.distance_squared(position)
+                .total_cmp(&b.distance_squared(position))
+        })
+        .copied()
+}
+
+/// The nearest button roughly in a direction, preferring ones in a straight line.
+fn closest_in_direction(
+    buttons: &[(Entity, Vec2)],
+    position: Vec2,
+    direction: Vec2,
+) -> Option<(Entity, Vec2)> {
+    let score = |other: Vec2| {
+        let offset = other - position;
+        let along = offset.dot(direction);
+        let across = offset.perp_dot(direction).abs();
+        along + across * 2.0
+    };
+
+    buttons
+        .iter()
+        .filter(|(_, other)| (*other - position).dot(direction) > 1.0)
+        .min_by(|(_, a), (_, b)| score(*a).total_cmp(&score(*b)))
+        .copied()
+}
+
+/// The button before or after this one, reading left to right and top to bottom.
+fn step_in_order(
+    buttons: &[(Entity, Vec2)],
+    current: (Entity, Vec2),
+    step: i32,
+) -> Option<(Entity, Vec2)> {
+    let mut ordered = buttons.to_vec();
+    ordered.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let index = ordered
+        .iter()
+        .position(|(entity, _)| *entity == current.0)? as i32;
+    let next = (index + step).rem_euclid(ordered.len() as i32);
+
+    ordered.get(next as usize).copied()
+}
+
+/// Presses buttons the same way a click does, so the menus' listeners don't need to know.
+fn press_focused(
+    input: Res<MenuInput>,
+    focus: Res<MenuFocus>,
+    mut interactions: Query<&mut Interaction>,
+    cancel_buttons: Query<Entity, With<CancelButton>>,
+    mut pressed: Local<Vec<Entity>>,
+) {
+    // nothing releases the buttons pressed last frame the way releasing the mouse would, which
+    // leaves the button hovered while it still has the focus
+    for entity in pressed.drain(..) {
+        if let Ok(mut interaction) = interactions.get_mut(entity) {
+            if *interaction == Interaction::Pressed {
+                *interaction = if focus.entity == Some(entity) {
+                    Interaction::Hovered
+                } else {
+                    Interaction::None
+                };
+            }
+        }
+    }
+
+    let target = if input.cancel {
+        cancel_buttons.iter().next()
+    } else if input.confirm {
+        focus.entity
+    } else {
+        None
+    };
+
+    if let Some(entity) = target {
+        if let Ok(mut interaction) = interactions.get_mut(entity) {
+            *interaction = Interaction::Pressed;
+            pressed.push(entity);
+        }
+    }
+}
+
+fn outline_focused(
+    focus: Res<MenuFocus>,
+    outlined: Query<Entity, With<Outline>>,
+    buttons: Query<(), With<Button>>,
+    mut commands: Commands,
+) {
+    if focus.is_changed() {
+        for entity in outlined.iter() {
+            if Some(entity) != focus.entity && buttons.contains(entity) {
+                commands.entity(entity).remove::<Outline>();
+            }
+        }
+
+        // the focused button may be despawned with its menu this frame
+        if let Some(entity) = focus.entity {
+            commands
+                .entity(entity)
+                .try_insert(Outline::new(Val::Px(3.0), Val::Px(2.0), FOCUS_OUTLINE));
+        }
+    }
+}
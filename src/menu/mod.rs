@@ -0,0 +1,507 @@
+mod controls;
+mod focus;
+pub mod profile;
+mod settings;
+
+use crate::level::{LevelState, RestartLevelEvent};
+use crate::menu::focus::{CancelButton, MenuFocus};
+use crate::menu::settings::SettingsMenuButton;
+use crate::save::Profile;
+use crate::timer::{format_delta, format_ticks, SpeedrunTimer};
+use crate::AppState;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+pub struct MenuPlugin;
+
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+const FOCUSED_BUTTON: Color = Color::rgb(0.25, 0.3, 0.4);
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                manage_main_menu,
+                manage_pause_menu,
+                manage_game_over_menu,
+                manage_level_complete_menu,
+            ),
+        )
+        .add_systems(Update, button_background)
+        .add_systems(Update, (start_listener, profile_menu_listener))
+        .add_systems(
+            Update,
+            (
+                resume_listener,
+                main_menu_listener,
+                retry_listener,
+                ghost_toggle_listener,
+            ),
+        );
+        profile::setup(app);
+        settings::setup(app);
+        controls::setup(app);
+        focus::setup(app);
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct MainMenu;
+
+/// Starts the level at this asset path.
+#[derive(Default, Debug, Clone, Component)]
+pub struct StartGameButton {
+    pub level: String,
+}
+
+/// Opens the profile select screen.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct ProfileMenuButton;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct PauseMenu;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct ResumeGameButton;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct MainMenuButton;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct GhostToggleButton;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct GameOverMenu;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct RetryButton;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct LevelCompleteMenu;
+
+fn manage_main_menu(
+    app_state: Res<State<AppState>>,
+    main_menu_query: Query<Entity, With<MainMenu>>,
+    profile: Res<Profile>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    // switching profiles changes which levels are unlocked
+    if app_state.is_changed() || profile.is_changed() {
+        if let Some(menu) = main_menu_query.iter().next() {
+            commands.entity(menu).despawn_recursive();
+        }
+
+        if *app_state.get() == AppState::MainMenu {
+            commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(MainMenu)
+                .with_children(|parent| {
+                    spawn_button(
+                        parent,
+                        Val::Px(400.0), Val::Px(65.0),
+                        format!("Profile: {}", profile.name),
+                        &assets,
+                    )
+                    .insert(ProfileMenuButton);
+                    spawn_button(
+                        parent,
+                        Val::Px(400.0), Val::Px(65.0),
+                        "Settings",
+                        &assets,
+                    )
+                    .insert(SettingsMenuButton);
+
+                    for level in profile.unlocked_levels.iter() {
+                        let label = if profile.completed_levels.contains(level) {
+                            format!("{} *", level_name(level))
+                        } else {
+                            level_name(level).to_string()
+                        };
+
+                        spawn_button(
+                            parent,
+                            Val::Px(250.0), Val::Px(65.0),
+                            label,
+                            &assets,
+                        )
+                        .insert(StartGameButton {
+                            level: level.clone(),
+                        });
+                    }
+                });
+        }
+    }
+}
+
+fn manage_pause_menu(
+    app_state: Res<State<AppState>>,
+    pause_menu_query: Query<Entity, With<PauseMenu>>,
+    profile: Res<Profile>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    if app_state.is_changed() {
+        if *app_state.get() == AppState::PauseMenu {
+            if pause_menu_query.is_empty() {
+                commands
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(PauseMenu)
+                    .with_children(|parent| {
+                        spawn_button(
+                            parent,
+                            Val::Px(200.0), Val::Px(65.0),
+                            "Resume",
+                            &assets,
+                        )
+                        .insert(ResumeGameButton)
+                        .insert(CancelButton);
+                        spawn_button(
+                            parent,
+                            Val::Px(200.0), Val::Px(65.0),
+                            ghost_toggle_text(profile.settings.ghost),
+                            &assets,
+                        )
+                        .insert(GhostToggleButton);
+                        spawn_button(
+                            parent,
+                            Val::Px(200.0), Val::Px(65.0),
+                            "Settings",
+                            &assets,
+                        )
+                        .insert(SettingsMenuButton);
+                        spawn_button(
+                            parent,
+                            Val::Px(200.0), Val::Px(65.0),
+                            "Main Menu",
+                            &assets,
+                        )
+                        .insert(MainMenuButton);
+                    });
+            }
+        } else {
+            if let Some(menu) = pause_menu_query.iter().next() {
+                commands.entity(menu).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn manage_game_over_menu(
+    app_state: Res<State<AppState>>,
+    game_over_menu_query: Query<Entity, With<GameOverMenu>>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    if app_state.is_changed() {
+        if *app_state.get() == AppState::GameOver {
+            if game_over_menu_query.is_empty() {
+                commands
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(GameOverMenu)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            "Game Over",
+                            TextStyle {
+                                font: assets.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 80.0,
+                                color: Color::rgb(0.9, 0.2, 0.2),
+                                ..default()
+                            },
+                        ));
+                        spawn_button(
+                            parent,
+                            Val::Px(200.0), Val::Px(65.0),
+                            "Retry",
+                            &assets,
+                        )
+                        .insert(RetryButton);
+                        spawn_button(
+                            parent,
+                            Val::Px(200.0), Val::Px(65.0),
+                            "Main Menu",
+                            &assets,
+                        )
+                        .insert(MainMenuButton);
+                    });
+            }
+        } else {
+            if let Some(menu) = game_over_menu_query.iter().next() {
+                commands.entity(menu).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn manage_level_complete_menu(
+    app_state: Res<State<AppState>>,
+    level_complete_menu_query: Query<Entity, With<LevelCompleteMenu>>,
+    timer: Res<SpeedrunTimer>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    if app_state.is_changed() {
+        if *app_state.get() == AppState::LevelComplete {
+            if level_complete_menu_query.is_empty() {
+                let result = if timer.new_best {
+                    "New personal best!".to_string()
+                } else if let Some(delta) = timer.delta {
+                    format!("{} against your best", format_delta(delta))
+                } else {
+                    String::new()
+                };
+
+                commands
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(LevelCompleteMenu)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            "Level Complete",
+                            TextStyle {
+                                font: assets.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 80.0,
+                                color: Color::rgb(1.0, 0.8, 0.2),
+                                ..default()
+                            },
+                        ));
+                        parent.spawn(
+                            TextBundle::from_section(
+                                format!("{}\n{}", format_ticks(timer.ticks), result),
+                                TextStyle {
+                                    font: assets.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 32.0,
+                                    color: Color::rgb(0.9, 0.9, 0.9),
+                                    ..default()
+                                },
+                            )
+                            .with_text_alignment(TextAlignment::Center),
+                        );
+                        spawn_button(
+                            parent,
+                            Val::Px(200.0), Val::Px(65.0),
+                            "Retry",
+                            &assets,
+                        )
+                        .insert(RetryButton);
+                        spawn_button(
+                            parent,
+                            Val::Px(200.0), Val::Px(65.0),
+                            "Main Menu",
+                            &assets,
+                        )
+                        .insert(MainMenuButton);
+                    });
+            }
+        } else {
+            if let Some(menu) = level_complete_menu_query.iter().next() {
+                commands.entity(menu).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn spawn_button<'a, 'w, 's>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    width: Val,
+    height: Val,
+    text: impl Into<String>,
+    assets: &Res<AssetServer>,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut commands = parent.spawn(ButtonBundle {
+        background_color: NORMAL_BUTTON.into(),
+        style: Style {
+            width,
+            height,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        ..default()
+    });
+    commands.with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            text,
+            TextStyle {
+                font: assets.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 40.0,
+                color: Color::rgb(0.9, 0.9, 0.9),
+                ..default()
+            },
+        ));
+    });
+    commands
+}
+
+fn button_background(
+    focus: Res<MenuFocus>,
+    mut buttons: Query<(Entity, Ref<Interaction>, &mut BackgroundColor), With<Button>>,
+) {
+    for (entity, interaction, mut color) in buttons.iter_mut() {
+        if !focus.is_changed() && !interaction.is_changed() {
+            continue;
+        }
+
+        match *interaction {
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None if focus.entity == Some(entity) => {
+                *color = FOCUSED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+            Interaction::Pressed => {}
+        }
+    }
+}
+
+/// The file name of a level without its extension, like `level0`.
+fn level_name(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.split('.').next().unwrap_or(name)
+}
+
+fn start_listener(
+    start_game: Query<(&Interaction, &StartGameButton), Changed<Interaction>>,
+    mut level_state: ResMut<LevelState>,
+    mut app_state: ResMut<NextState<AppState>>,
+    assets: Res<AssetServer>,
+) {
+    for (&interaction, button) in start_game.iter() {
+        if interaction == Interaction::Pressed {
+            app_state.set(AppState::Loading);
+            level_state.handle = Some(assets.load(button.level.clone()));
+            return;
+        }
+    }
+}
+
+fn profile_menu_listener(
+    profile_menu: Query<&Interaction, (Changed<Interaction>, With<ProfileMenuButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for &interaction in profile_menu.iter() {
+        if interaction == Interaction::Pressed {
+            app_state.set(AppState::ProfileMenu);
+            return;
+        }
+    }
+}
+
+fn resume_listener(
+    resume_game: Query<&Interaction, (Changed<Interaction>, With<ResumeGameButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for &interaction in resume_game.iter() {
+        if interaction == Interaction::Pressed {
+            app_state.set(AppState::InGame);
+            return;
+        }
+    }
+}
+
+fn main_menu_listener(
+    main_menu: Query<&Interaction, (Changed<Interaction>, With<MainMenuButton>)>,
+    mut level_state: ResMut<LevelState>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for &interaction in main_menu.iter() {
+        if interaction == Interaction::Pressed {
+            app_state.set(AppState::MainMenu);
+            level_state.handle = None;
+            return;
+        }
+    }
+}
+
+fn retry_listener(
+    retry: Query<&Interaction, (Changed<Interaction>, With<RetryButton>)>,
+    mut restart_events: EventWriter<RestartLevelEvent>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for &interaction in retry.iter() {
+        if interaction == Interaction::Pressed {
+            app_state.set(AppState::Loading);
+            restart_events.send(RestartLevelEvent);
+            return;
+        }
+    }
+}
+
+fn ghost_toggle_text(enabled: bool) -> &'static str {
+    if enabled {
+        "Ghost: On"
+    } else {
+        "Ghost: Off"
+    }
+}
+
+fn ghost_toggle_listener(
+    ghost_toggle: Query<
+        (&Interaction, &Children),
+        (Changed<Interaction>, With<GhostToggleButton>),
+    >,
+    mut text: Query<&mut Text>,
+    mut profile: ResMut<Profile>,
+) {
+    for (&interaction, children) in ghost_toggle.iter() {
+        if interaction == Interaction::Pressed {
+            profile.settings.ghost = !profile.settings.ghost;
+            if let Err(err) = profile.save() {
+                error!("Error saving profile: {}", err);
+            }
+
+            let label = ghost_toggle_text(profile.settings.ghost);
+
+            for &child in children.iter() {
+                if let Ok(mut text) = text.get_mut(child) {
+                    text.sections[0].value = label.to_string();
+                }
+            }
+            return;
+        }
+    }
+}
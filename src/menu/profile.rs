@@ -0,0 +1,343 @@
+//! The profile select screen.
+
+use super::focus::{CancelButton, MenuFocus};
+use super::spawn_button;
+use crate::save::{Profile, ProfileEvent, ProfileList};
+use crate::AppState;
+use bevy::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.add_systems(Update, manage_profile_menu)
+        .add_systems(
+            Update,
+            (
+                select_listener,
+                new_profile_listener,
+                copy_listener,
+                delete_listener,
+                back_listener,
+            ),
+        );
+    setup_renaming(app);
+}
+
+/// Typing in a new name for a profile, kept apart from the rest of the menu so it can be tested
+/// without drawing anything.
+pub fn setup_renaming(app: &mut App) {
+    app.init_resource::<RenamingProfile>()
+        .add_systems(
+            Update,
+            (
+                // the enter that pressed the rename button mustn't also finish the rename
+                type_profile_name
+                    .run_if(in_state(AppState::ProfileMenu))
+                    .before(rename_listener),
+                rename_listener,
+            ),
+        )
+        .add_systems(OnExit(AppState::ProfileMenu), stop_renaming);
+}
+
+/// The longest name a profile can be given.
+const MAX_NAME_LEN: usize = 20;
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct ProfileMenu;
+
+#[derive(Default, Debug, Clone, Component)]
+pub struct SelectProfileButton {
+    pub id: String,
+}
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct NewProfileButton;
+
+#[derive(Default, Debug, Clone, Component)]
+pub struct RenameProfileButton {
+    pub id: String,
+}
+
+#[derive(Default, Debug, Clone, Component)]
+pub struct CopyProfileButton {
+    pub id: String,
+}
+
+/// Deletes a profile on the second press.
+#[derive(Default, Debug, Clone, Component)]
+pub struct DeleteProfileButton {
+    pub id: String,
+    pub confirming: bool,
+}
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct BackButton;
+
+/// The profile whose name is being typed in, if any.
+#[derive(Default, Debug, Clone, Resource)]
+pub struct RenamingProfile {
+    pub id: Option<String>,
+    pub name: String,
+}
+
+fn manage_profile_menu(
+    app_state: Res<State<AppState>>,
+    profile_menu_query: Query<Entity, With<ProfileMenu>>,
+    profile: Res<Profile>,
+    list: Res<ProfileList>,
+    renaming: Res<RenamingProfile>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    if app_state.is_changed()
+        || profile.is_changed()
+        || list.is_changed()
+        || renaming.is_changed()
+    {
+        if let Some(menu) = profile_menu_query.iter().next() {
+            commands.entity(menu).despawn_recursive();
+        }
+
+        if *app_state.get() == AppState::ProfileMenu {
+            commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(ProfileMenu)
+                .with_children(|parent| {
+                    for summary in list.profiles.iter() {
+                        let label = if renaming.id.as_ref() == Some(&summary.id) {
+                            format!("{}_", renaming.name)
+                        } else if summary.id == profile.id {
+                            format!("> {}", summary.name)
+                        } else {
+                            summary.name.clone()
+                        };
+
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Row,
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                spawn_button(
+                                    parent,
+                                    Val::Px(400.0), Val::Px(65.0),
+                                    label,
+                                    &assets,
+                                )
+                                .insert(SelectProfileButton {
+                                    id: summary.id.clone(),
+                                });
+                                spawn_button(
+                                    parent,
+                                    Val::Px(150.0), Val::Px(65.0),
+                                    "Rename",
+                                    &assets,
+                                )
+                                .insert(RenameProfileButton {
+                                    id: summary.id.clone(),
+                                });
+                                spawn_button(
+                                    parent,
+                                    Val::Px(150.0), Val::Px(65.0),
+                                    "Copy",
+                                    &assets,
+                                )
+                                .insert(CopyProfileButton {
+                                    id: summary.id.clone(),
+                                });
+                                spawn_button(
+                                    parent,
+                                    Val::Px(150.0), Val::Px(65.0),
+                                    "Delete",
+                                    &assets,
+                                )
+                                .insert(DeleteProfileButton {
+                                    id: summary.id.clone(),
+                                    confirming: false,
+                                });
+                            });
+                    }
+
+                    spawn_button(
+                        parent,
+                        Val::Px(250.0), Val::Px(65.0),
+                        "New Profile",
+                        &assets,
+                    )
+                    .insert(NewProfileButton);
+                    spawn_button(
+                        parent,
+                        Val::Px(250.0), Val::Px(65.0),
+                        "Back",
+                        &assets,
+                    )
+                    .insert(BackButton)
+                    .insert(CancelButton);
+                });
+        }
+    }
+}
+
+/// Types into the name of the profile being renamed. Enter keeps the name, escape throws it away.
+fn type_profile_name(
+    mut renaming: ResMut<RenamingProfile>,
+    mut chars: EventReader<ReceivedCharacter>,
+    key: Res<Input<KeyCode>>,
+    mut profile_events: EventWriter<ProfileEvent>,
+) {
+    let Some(id) = renaming.id.clone() else {
+        chars.clear();
+        return;
+    };
+
+    for event in chars.read() {
+        if !event.char.is_control() && renaming.name.chars().count() < MAX_NAME_LEN {
+            renaming.name.push(event.char);
+        }
+    }
+
+    if key.just_pressed(KeyCode::Back) {
+        renaming.name.pop();
+    }
+
+    if key.just_pressed(KeyCode::Return) {
+        let name = renaming.name.trim().to_string();
+        if !name.is_empty() {
+            profile_events.send(ProfileEvent::Rename { id, name });
+        }
+        *renaming = RenamingProfile::default();
+    } else if key.just_pressed(KeyCode::Escape) {
+        *renaming = RenamingProfile::default();
+    }
+}
+
+pub fn is_renaming(renaming: Res<RenamingProfile>) -> bool {
+    renaming.id.is_some()
+}
+
+fn stop_renaming(mut renaming: ResMut<RenamingProfile>) {
+    *renaming = RenamingProfile::default();
+}
+
+fn select_listener(
+    select: Query<(&Interaction, &SelectProfileButton), Changed<Interaction>>,
+    mut profile_events: EventWriter<ProfileEvent>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (&interaction, button) in select.iter() {
+        if interaction == Interaction::Pressed {
+            profile_events.send(ProfileEvent::Select {
+                id: button.id.clone(),
+            });
+            app_state.set(AppState::MainMenu);
+            return;
+        }
+    }
+}
+
+fn new_profile_listener(
+    new_profile: Query<&Interaction, (Changed<Interaction>, With<NewProfileButton>)>,
+    mut profile_events: EventWriter<ProfileEvent>,
+) {
+    for &interaction in new_profile.iter() {
+        if interaction == Interaction::Pressed {
+            profile_events.send(ProfileEvent::Create);
+            return;
+        }
+    }
+}
+
+fn rename_listener(
+    rename: Query<(&Interaction, &RenameProfileButton), Changed<Interaction>>,
+    list: Res<ProfileList>,
+    mut renaming: ResMut<RenamingProfile>,
+) {
+    for (&interaction, button) in rename.iter() {
+        if interaction == Interaction::Pressed {
+            let name = list
+                .profiles
+                .iter()
+                .find(|profile| profile.id == button.id)
+                .map(|profile| profile.name.clone())
+                .unwrap_or_default();
+
+            *renaming = RenamingProfile {
+                id: Some(button.id.clone()),
+                name,
+            };
+            return;
+        }
+    }
+}
+
+fn copy_listener(
+    copy: Query<(&Interaction, &CopyProfileButton), Changed<Interaction>>,
+    mut profile_events: EventWriter<ProfileEvent>,
+) {
+    for (&interaction, button) in copy.iter() {
+        if interaction == Interaction::Pressed {
+            profile_events.send(ProfileEvent::Copy {
+                id: button.id.clone(),
+            });
+            return;
+        }
+    }
+}
+
+fn delete_listener(
+    mut delete: Query<(Entity, Ref<Interaction>, &mut DeleteProfileButton, &Children)>,
+    focus: Res<MenuFocus>,
+    mut text: Query<&mut Text>,
+    mut profile_events: EventWriter<ProfileEvent>,
+) {
+    for (entity, interaction, mut button, children) in delete.iter_mut() {
+        if interaction.is_changed() && *interaction == Interaction::Pressed {
+            if button.confirming {
+                profile_events.send(ProfileEvent::Delete {
+                    id: button.id.clone(),
+                });
+                return;
+            }
+            button.confirming = true;
+        } else if button.confirming
+            && *interaction == Interaction::None
+            && focus.entity != Some(entity)
+        {
+            // moving the mouse or the focus away from the button cancels the delete
+            button.confirming = false;
+        } else {
+            continue;
+        }
+
+        let label = if button.confirming { "Sure?" } else { "Delete" };
+        for &child in children.iter() {
+            if let Ok(mut text) = text.get_mut(child) {
+                text.sections[0].value = label.to_string();
+            }
+        }
+    }
+}
+
+fn back_listener(
+    back: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for &interaction in back.iter() {
+        if interaction == Interaction::Pressed {
+            app_state.set(AppState::MainMenu);
+            return;
+        }
+    }
+}
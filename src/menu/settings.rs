@@ -0,0 +1,333 @@
+//! The settings screen, reachable from the main and pause menus.
+
+use super::controls::ControlsMenuButton;
+use super::focus::CancelButton;
+use super::spawn_button;
+use crate::player::ball::BallType;
+use crate::player::camera::{CameraMode, MAX_CAMERA_DISTANCE, MIN_CAMERA_DISTANCE};
+use crate::save::{Profile, Settings, ShadowQuality};
+use crate::AppState;
+use bevy::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.init_resource::<SettingsMenuReturn>()
+        .add_systems(Update, manage_settings_menu)
+        .add_systems(
+            Update,
+            (settings_menu_listener, setting_listener, settings_back_listener),
+        );
+}
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct SettingsMenu;
+
+/// Opens the settings screen.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct SettingsMenuButton;
+
+/// Changes a setting by one step, down for -1 and up for 1.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct SettingButton {
+    pub setting: Setting,
+    pub step: i32,
+}
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct SettingsBackButton;
+
+/// The menu to go back to when leaving the settings screen.
+#[derive(Default, Debug, Copy, Clone, Resource)]
+struct SettingsMenuReturn {
+    state: AppState,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Setting {
+    MouseSensitivity,
+    InvertY,
+    StickSensitivity,
+    StickDeadzone,
+    CameraDistance,
+    CameraMode,
+    AutoRecenter,
+    Fov,
+    Fullscreen,
+    Vsync,
+    Bloom,
+    Shadows,
+    Volume,
+    Ball,
+}
+
+impl Setting {
+    const ALL: [Setting; 14] = [
+        Setting::MouseSensitivity,
+        Setting::InvertY,
+        Setting::StickSensitivity,
+        Setting::StickDeadzone,
+        Setting::CameraDistance,
+        Setting::CameraMode,
+        Setting::AutoRecenter,
+        Setting::Fov,
+        Setting::Fullscreen,
+        Setting::Vsync,
+        Setting::Bloom,
+        Setting::Shadows,
+        Setting::Volume,
+        Setting::Ball,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Setting::MouseSensitivity => "Mouse Sensitivity",
+            Setting::InvertY => "Invert Y",
+            Setting::StickSensitivity => "Stick Sensitivity",
+            Setting::StickDeadzone => "Stick Deadzone",
+            Setting::CameraDistance => "Camera Distance",
+            Setting::CameraMode => "Camera Mode",
+            Setting::AutoRecenter => "Auto Recenter",
+            Setting::Fov => "Field of View",
+            Setting::Fullscreen => "Fullscreen",
+            Setting::Vsync => "VSync",
+            Setting::Bloom => "Bloom",
+            Setting::Shadows => "Shadows",
+            Setting::Volume => "Volume",
+            Setting::Ball => "Ball",
+        }
+    }
+
+    fn value(self, settings: &Settings) -> String {
+        match self {
+            Setting::MouseSensitivity => format!("{:.1}", settings.mouse_sensitivity),
+            Setting::InvertY => on_off(settings.invert_y),
+            Setting::StickSensitivity => format!("{:.1}", settings.stick_sensitivity),
+            Setting::StickDeadzone => format!("{:.0}%", settings.stick_deadzone * 100.0),
+            Setting::CameraDistance => format!("{:.1}", settings.camera_distance),
+            Setting::CameraMode => settings.camera_mode.name().to_string(),
+            Setting::AutoRecenter => on_off(settings.auto_recenter),
+            Setting::Fov => format!("{:.0}", settings.fov),
+            Setting::Fullscreen => on_off(settings.fullscreen),
+            Setting::Vsync => on_off(settings.vsync),
+            Setting::Bloom => on_off(settings.bloom),
+            Setting::Shadows => format!("{:?}", settings.shadows),
+            Setting::Volume => format!("{:.0}%", settings.volume * 100.0),
+            Setting::Ball => settings.ball.name().to_string(),
+        }
+    }
+
+    fn adjust(self, settings: &mut Settings, step: i32) {
+        match self {
+            Setting::MouseSensitivity => {
+                settings.mouse_sensitivity =
+                    adjust_value(settings.mouse_sensitivity, step, 0.1, 0.1, 5.0)
+            }
+            Setting::InvertY => settings.invert_y = !settings.invert_y,
+            Setting::StickSensitivity => {
+                settings.stick_sensitivity =
+                    adjust_value(settings.stick_sensitivity, step, 0.1, 0.1, 5.0)
+            }
+            Setting::StickDeadzone => {
+                settings.stick_deadzone =
+                    adjust_value(settings.stick_deadzone, step, 0.05, 0.0, 0.5)
+            }
+            Setting::CameraDistance => {
+                settings.camera_distance = adjust_value(
+                    settings.camera_distance,
+                    step,
+                    0.5,
+                    MIN_CAMERA_DISTANCE,
+                    MAX_CAMERA_DISTANCE,
+                )
+            }
+            Setting::CameraMode => {
+                let count = CameraMode::ALL.len() as i32;
+                let index = CameraMode::ALL
+                    .iter()
+                    .position(|&mode| mode == settings.camera_mode)
+                    .unwrap_or(0) as i32;
+                settings.camera_mode = CameraMode::ALL[(index + step).rem_euclid(count) as usize];
+            }
+            Setting::AutoRecenter => settings.auto_recenter = !settings.auto_recenter,
+            Setting::Fov => settings.fov = adjust_value(settings.fov, step, 5.0, 30.0, 120.0),
+            Setting::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Setting::Vsync => settings.vsync = !settings.vsync,
+            Setting::Bloom => settings.bloom = !settings.bloom,
+            Setting::Shadows => {
+                const QUALITIES: [ShadowQuality; 4] = [
+                    ShadowQuality::Off,
+                    ShadowQuality::Low,
+                    ShadowQuality::Medium,
+                    ShadowQuality::High,
+                ];
+                let index = QUALITIES
+                    .iter()
+                    .position(|&quality| quality == settings.shadows)
+                    .unwrap_or(0) as i32;
+                settings.shadows = QUALITIES[(index + step).clamp(0, 3) as usize];
+            }
+            Setting::Volume => {
+                settings.volume = adjust_value(settings.volume, step, 0.1, 0.0, 1.0)
+            }
+            Setting::Ball => {
+                let count = BallType::ALL.len() as i32;
+                let index = BallType::ALL
+                    .iter()
+                    .position(|&ball| ball == settings.ball)
+                    .unwrap_or(0) as i32;
+                settings.ball = BallType::ALL[(index + step).rem_euclid(count) as usize];
+            }
+        }
+    }
+}
+
+fn on_off(value: bool) -> String {
+    if value { "On" } else { "Off" }.to_string()
+}
+
+/// Steps a value, rounding to the step so repeated changes don't drift.
+fn adjust_value(value: f32, step: i32, size: f32, min: f32, max: f32) -> f32 {
+    ((value / size).round() * size + step as f32 * size).clamp(min, max)
+}
+
+fn manage_settings_menu(
+    app_state: Res<State<AppState>>,
+    settings_menu_query: Query<Entity, With<SettingsMenu>>,
+    profile: Res<Profile>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    if app_state.is_changed() || profile.is_changed() {
+        if let Some(menu) = settings_menu_query.iter().next() {
+            commands.entity(menu).despawn_recursive();
+        }
+
+        if *app_state.get() == AppState::SettingsMenu {
+            let text_style = TextStyle {
+                font: assets.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 32.0,
+                color: Color::rgb(0.9, 0.9, 0.9),
+                ..default()
+            };
+
+            commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(SettingsMenu)
+                .with_children(|parent| {
+                    for setting in Setting::ALL {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Row,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(
+                                    TextBundle::from_section(setting.label(), text_style.clone())
+                                        .with_style(Style {
+                                            width: Val::Px(300.0),
+                                            ..default()
+                                        }),
+                                );
+                                spawn_button(
+                                    parent,
+                                    Val::Px(40.0), Val::Px(40.0),
+                                    "<",
+                                    &assets,
+                                )
+                                .insert(SettingButton { setting, step: -1 });
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        setting.value(&profile.settings),
+                                        text_style.clone(),
+                                    )
+                                    .with_text_alignment(TextAlignment::Center)
+                                    .with_style(Style {
+                                        width: Val::Px(150.0),
+                                        ..default()
+                                    }),
+                                );
+                                spawn_button(
+                                    parent,
+                                    Val::Px(40.0), Val::Px(40.0),
+                                    ">",
+                                    &assets,
+                                )
+                                .insert(SettingButton { setting, step: 1 });
+                            });
+                    }
+
+                    spawn_button(
+                        parent,
+                        Val::Px(200.0), Val::Px(65.0),
+                        "Controls",
+                        &assets,
+                    )
+                    .insert(ControlsMenuButton);
+                    spawn_button(
+                        parent,
+                        Val::Px(200.0), Val::Px(65.0),
+                        "Back",
+                        &assets,
+                    )
+                    .insert(SettingsBackButton)
+                    .insert(CancelButton);
+                });
+        }
+    }
+}
+
+fn settings_menu_listener(
+    settings_menu: Query<&Interaction, (Changed<Interaction>, With<SettingsMenuButton>)>,
+    cur_state: Res<State<AppState>>,
+    mut settings_return: ResMut<SettingsMenuReturn>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for &interaction in settings_menu.iter() {
+        if interaction == Interaction::Pressed {
+            settings_return.state = *cur_state.get();
+            app_state.set(AppState::SettingsMenu);
+            return;
+        }
+    }
+}
+
+fn setting_listener(
+    setting_buttons: Query<(&Interaction, &SettingButton), Changed<Interaction>>,
+    mut profile: ResMut<Profile>,
+) {
+    for (&interaction, button) in setting_buttons.iter() {
+        if interaction == Interaction::Pressed {
+            button.setting.adjust(&mut profile.settings, button.step);
+            if let Err(err) = profile.save() {
+                error!("Error saving profile: {}", err);
+            }
+            return;
+        }
+    }
+}
+
+fn settings_back_listener(
+    back: Query<&Interaction, (Changed<Interaction>, With<SettingsBackButton>)>,
+    settings_return: Res<SettingsMenuReturn>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for &interaction in back.iter() {
+        if interaction == Interaction::Pressed {
+            app_state.set(settings_return.state);
+            return;
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::level::LevelRemovedEvent;
+use crate::player::remove_player;
+use crate::{AppState, PHYSICS_TICK_RATE};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Sets up rapier, either stepping once per fixed update tick or once per frame.
+pub struct PhysicsPlugin {
+    /// Steps rapier by exactly one tick in [`FixedUpdate`] so that the same input always gives the
+    /// same result. Otherwise rapier steps by the frame time after [`Update`].
+    pub deterministic: bool,
+}
+
+impl Default for PhysicsPlugin {
+    fn default() -> Self {
+        PhysicsPlugin {
+            deterministic: !std::env::args().any(|arg| arg == "--variable-timestep"),
+        }
+    }
+}
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        if self.deterministic {
+            app.insert_resource(RapierConfiguration {
+                timestep_mode: TimestepMode::Fixed {
+                    dt: (1.0 / PHYSICS_TICK_RATE) as f32,
+                    substeps: 1,
+                },
+                ..default()
+            })
+            .add_plugins(
+                RapierPhysicsPlugin::<NoUserData>::default().with_default_system_setup(false),
+            )
+            .configure_sets(
+                FixedUpdate,
+                (
+                    PhysicsSet::SyncBackend,
+                    PhysicsSet::SyncBackendFlush,
+                    PhysicsSet::StepSimulation,
+                    PhysicsSet::Writeback,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend)
+                        .in_set(PhysicsSet::SyncBackend),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackendFlush)
+                        .in_set(PhysicsSet::SyncBackendFlush),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::StepSimulation)
+                        .in_set(PhysicsSet::StepSimulation),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::Writeback)
+                        .in_set(PhysicsSet::Writeback),
+                ),
+            );
+
+            info!(
+                "Using deterministic physics at {} ticks per second.",
+                PHYSICS_TICK_RATE
+            );
+        } else {
+            app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+
+            info!("Using variable timestep physics.");
+        }
+
+        app.insert_resource(Time::<Fixed>::from_hz(PHYSICS_TICK_RATE))
+            .add_systems(Startup, setup_physics)
+            .add_systems(OnEnter(AppState::InGame), resume_physics)
+            .add_systems(OnExit(AppState::InGame), pause_physics)
+            .add_systems(Update, reset_physics_world.after(remove_player));
+    }
+}
+
+fn setup_physics(mut physics: ResMut<RapierConfiguration>) {
+    physics.physics_pipeline_active = false;
+}
+
+// these run during the state transition so that no physics tick ever runs with the wrong state
+
+fn resume_physics(mut physics: ResMut<RapierConfiguration>) {
+    physics.physics_pipeline_active = true;
+}
+
+fn pause_physics(mut physics: ResMut<RapierConfiguration>) {
+    physics.physics_pipeline_active = false;
+}
+
+/// Throws away rapier's world once a level has been torn down.
+///
+/// Rapier hands out body and collider handles from free lists, and the order it solves contacts in
+/// depends on those handles, so a level rebuilt into a used world can behave slightly differently
+/// from the same level built into a fresh one.
+fn reset_physics_world(
+    mut rapier_context: ResMut<RapierContext>,
+    mut level_remove: EventReader<LevelRemovedEvent>,
+) {
+    if level_remove.read().next().is_some() {
+        *rapier_context = RapierContext::default();
+    }
+
+    level_remove.clear();
+}
@@ -0,0 +1,247 @@
+//! Things the ball can do besides rolling and jumping, unlocked by each level.
+
+use super::camera::PlayerCamera;
+use super::config::PlayerConfig;
+use super::{jump_player, player_exists, Grounded, JumpBuffer, Player, PlayerInput};
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub fn setup(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        use_abilities
+            .after(jump_player)
+            .before(PhysicsSet::SyncBackend)
+            .run_if(player_exists)
+            .run_if(in_state(AppState::InGame)),
+    );
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AbilityKind {
+    /// A burst of speed in the direction the player is moving.
+    Dash,
+    /// A second jump in the air.
+    DoubleJump,
+    /// Slams straight down from the air.
+    GroundPound,
+    /// Stops the ball quickly while held on the ground.
+    Brake,
+}
+
+/// When an ability can be used.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Condition {
+    Always,
+    Grounded,
+    Airborne,
+}
+
+/// How an ability's input triggers it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Trigger {
+    /// Once per press.
+    Press,
+    /// Every tick the input is held.
+    Hold,
+}
+
+/// The rules an ability is used by.
+#[derive(Debug, Copy, Clone)]
+pub struct AbilityInfo {
+    /// Time between uses, in seconds.
+    pub cooldown: f32,
+    /// How many times the ability can be used before it has to recharge, or `None` for no limit.
+    pub charges: Option<u32>,
+    /// Whether charges come back on landing rather than one per cooldown.
+    pub recharge_on_landing: bool,
+    pub condition: Condition,
+    pub trigger: Trigger,
+}
+
+const DASH_SPEED: f32 = 9.0;
+const GROUND_POUND_SPEED: f32 = 15.0;
+/// The fraction of the ball's speed braking takes away per second.
+const BRAKE_RATE: f32 = 4.0;
+
+impl AbilityKind {
+    pub fn info(self) -> AbilityInfo {
+        match self {
+            AbilityKind::Dash => AbilityInfo {
+                cooldown: 1.0,
+                charges: Some(1),
+                recharge_on_landing: false,
+                condition: Condition::Always,
+                trigger: Trigger::Press,
+            },
+            AbilityKind::DoubleJump => AbilityInfo {
+                cooldown: 0.0,
+                charges: Some(1),
+                recharge_on_landing: true,
+                condition: Condition::Airborne,
+                trigger: Trigger::Press,
+            },
+            AbilityKind::GroundPound => AbilityInfo {
+                cooldown: 0.5,
+                charges: Some(1),
+                recharge_on_landing: true,
+                condition: Condition::Airborne,
+                trigger: Trigger::Press,
+            },
+            AbilityKind::Brake => AbilityInfo {
+                cooldown: 0.0,
+                charges: None,
+                recharge_on_landing: false,
+                condition: Condition::Grounded,
+                trigger: Trigger::Hold,
+            },
+        }
+    }
+
+    /// Whether the player is holding the input for this ability.
+    pub fn input(self, input: &PlayerInput) -> bool {
+        match self {
+            AbilityKind::Dash => input.dash,
+            AbilityKind::DoubleJump => input.jump,
+            AbilityKind::GroundPound => input.ground_pound,
+            AbilityKind::Brake => input.brake,
+        }
+    }
+}
+
+/// One of the player's abilities and how ready it is.
+#[derive(Debug, Clone)]
+pub struct Ability {
+    pub kind: AbilityKind,
+    pub charges: Option<u32>,
+    /// Time until the ability can be used again, in seconds.
+    pub cooldown: f32,
+    was_held: bool,
+}
+
+impl Ability {
+    pub fn new(kind: AbilityKind) -> Ability {
+        Ability {
+            kind,
+            charges: kind.info().charges,
+            cooldown: 0.0,
+            was_held: false,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown <= 0.0 && self.charges != Some(0)
+    }
+}
+
+/// The abilities the player has in the current level.
+#[derive(Default, Debug, Clone, Component)]
+pub struct Abilities {
+    pub abilities: Vec<Ability>,
+}
+
+impl Abilities {
+    pub fn new(kinds: &[AbilityKind]) -> Abilities {
+        Abilities {
+            abilities: kinds.iter().map(|&kind| Ability::new(kind)).collect(),
+        }
+    }
+}
+
+fn use_abilities(
+    mut player: Query<(&mut Abilities, &mut Velocity, &Grounded, &mut JumpBuffer), With<Player>>,
+    camera: Query<&PlayerCamera>,
+    input: Res<PlayerInput>,
+    config: Res<PlayerConfig>,
+    rapier_config: Res<RapierConfiguration>,
+    time: Res<Time>,
+) {
+    let (mut abilities, mut velocity, grounded, mut jump_buffer) = player.single_mut();
+    let camera = camera.single();
+
+    // within coyote time a jump is still a normal jump
+    let airborne = !grounded.is_grounded() && grounded.time_in_air > config.coyote_time;
+
+    for ability in abilities.abilities.iter_mut() {
+        let info = ability.kind.info();
+
+        ability.cooldown = (ability.cooldown - time.delta_seconds()).max(0.0);
+        if let Some(charges) = &mut ability.charges {
+            let max_charges = info.charges.unwrap_or(0);
+            if info.recharge_on_landing && grounded.is_grounded() {
+                *charges = max_charges;
+            } else if !info.recharge_on_landing && ability.cooldown <= 0.0 && *charges < max_charges
+            {
+                // one charge comes back per cooldown
+                *charges += 1;
+                if *charges < max_charges {
+                    ability.cooldown = info.cooldown;
+                }
+            }
+        }
+
+        let held = ability.kind.input(&input);
+        let triggered = match info.trigger {
+            Trigger::Press => held && !ability.was_held,
+            Trigger::Hold => held,
+        };
+        ability.was_held = held;
+
+        let allowed = match info.condition {
+            Condition::Always => true,
+            Condition::Grounded => grounded.is_grounded(),
+            Condition::Airborne => airborne,
+        };
+
+        if !triggered || !allowed || !ability.is_ready() {
+            continue;
+        }
+
+        let used = match ability.kind {
+            AbilityKind::Dash => {
+                let movement = camera.get_looking() * input.movement.y
+                    + Vec3::cross(camera.get_looking(), Vec3::Y) * input.movement.x;
+                let horizontal = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z);
+                let direction = movement
+                    .try_normalize()
+                    .or(horizontal.try_normalize())
+                    .unwrap_or(camera.get_looking());
+
+                velocity.linvel += direction * DASH_SPEED;
+                true
+            }
+            AbilityKind::DoubleJump => {
+                // a press the ground jump already used doesn't count
+                if jump_buffer.pressed_for.is_some() {
+                    let jump_speed =
+                        (2.0 * rapier_config.gravity.length() * config.jump_height).sqrt();
+                    velocity.linvel.y = velocity.linvel.y.max(jump_speed);
+                    jump_buffer.pressed_for = None;
+                    true
+                } else {
+                    false
+                }
+            }
+            AbilityKind::GroundPound => {
+                velocity.linvel = Vec3::new(0.0, -GROUND_POUND_SPEED, 0.0);
+                velocity.angvel = Vec3::ZERO;
+                true
+            }
+            AbilityKind::Brake => {
+                let slow = (1.0 - BRAKE_RATE * time.delta_seconds()).max(0.0);
+                velocity.linvel.x *= slow;
+                velocity.linvel.z *= slow;
+                velocity.angvel *= slow;
+                true
+            }
+        };
+
+        if used {
+            ability.cooldown = info.cooldown;
+            if let Some(charges) = &mut ability.charges {
+                *charges = charges.saturating_sub(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! The different balls the player can roll around as.
+
+use super::config::PlayerConfig;
+use super::Player;
+use crate::level::logic::pickup::{collect_pickups, PickupEvent, PickupKind};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+pub fn setup(app: &mut App) {
+    app.init_resource::<SelectedBall>()
+        .add_systems(FixedUpdate, collect_ball_pickups.after(collect_pickups));
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Component, Serialize, Deserialize)]
+pub enum BallType {
+    #[default]
+    Normal,
+    /// Heavy and hard to get going, but hard to stop too.
+    Steel,
+    /// Light and floaty, and slowed down a lot by the air.
+    Paper,
+    Rubber,
+    /// Grips the ground hard enough to climb steep slopes.
+    Sticky,
+}
+
+impl BallType {
+    pub const ALL: [BallType; 5] = [
+        BallType::Normal,
+        BallType::Steel,
+        BallType::Paper,
+        BallType::Rubber,
+        BallType::Sticky,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BallType::Normal => "Normal",
+            BallType::Steel => "Steel",
+            BallType::Paper => "Paper",
+            BallType::Rubber => "Rubber",
+            BallType::Sticky => "Sticky",
+        }
+    }
+
+    pub fn properties(self) -> BallProperties {
+        let normal = BallProperties {
+            density: 1.0,
+            radius_scale: 1.0,
+            friction: 0.5,
+            restitution: 0.0,
+            damping_scale: 1.0,
+            torque_scale: 1.0,
+            stickiness: 0.0,
+            color: Color::rgb(0.0, 10.0, 12.0),
+            light_color: Color::rgb(0.0, 0.833, 1.0),
+            metallic: 0.0,
+            roughness: 0.5,
+        };
+
+        match self {
+            BallType::Normal => normal,
+            BallType::Steel => BallProperties {
+                density: 8.0,
+                radius_scale: 0.9,
+                friction: 0.6,
+                restitution: 0.05,
+                damping_scale: 0.4,
+                torque_scale: 5.0,
+                color: Color::rgb(1.5, 1.6, 1.8),
+                light_color: Color::rgb(0.8, 0.85, 1.0),
+                metallic: 1.0,
+                roughness: 0.2,
+                ..normal
+            },
+            BallType::Paper => BallProperties {
+                density: 0.1,
+                radius_scale: 1.1,
+                friction: 0.8,
+                damping_scale: 4.0,
+                torque_scale: 0.2,
+                color: Color::rgb(6.0, 6.0, 5.0),
+                light_color: Color::rgb(1.0, 1.0, 0.85),
+                roughness: 1.0,
+                ..normal
+            },
+            BallType::Rubber => BallProperties {
+                density: 1.2,
+                friction: 1.0,
+                restitution: 0.85,
+                torque_scale: 1.2,
+                color: Color::rgb(12.0, 1.0, 1.5),
+                light_color: Color::rgb(1.0, 0.1, 0.15),
+                roughness: 0.8,
+                ..normal
+            },
+            BallType::Sticky => BallProperties {
+                friction: 2.0,
+                damping_scale: 1.5,
+                stickiness: 15.0,
+                color: Color::rgb(3.0, 10.0, 1.0),
+                light_color: Color::rgb(0.3, 1.0, 0.1),
+                roughness: 0.9,
+                ..normal
+            },
+        }
+    }
+}
+
+/// How a type of ball differs from the normal one. Scales multiply the [`PlayerConfig`].
+#[derive(Debug, Copy, Clone)]
+pub struct BallProperties {
+    /// Mass per unit of volume, 1 for the normal ball.
+    pub density: f32,
+    pub radius_scale: f32,
+    pub friction: f32,
+    /// How bouncy the ball is, from 0 to 1.
+    pub restitution: f32,
+    pub damping_scale: f32,
+    pub torque_scale: f32,
+    /// How hard the ball is pulled into the ground it's on, as an acceleration.
+    pub stickiness: f32,
+    pub color: Color,
+    pub light_color: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+}
+
+impl BallProperties {
+    pub fn radius(&self, config: &PlayerConfig) -> f32 {
+        config.radius * self.radius_scale
+    }
+
+    pub fn mass(&self, config: &PlayerConfig) -> f32 {
+        self.density * 4.0 / 3.0 * PI * self.radius(config).powi(3)
+    }
+
+    pub fn damping(&self, config: &PlayerConfig) -> Damping {
+        Damping {
+            angular_damping: config.angular_damping * self.damping_scale,
+            linear_damping: config.linear_damping * self.damping_scale,
+        }
+    }
+
+    pub fn material(&self) -> StandardMaterial {
+        StandardMaterial {
+            base_color: self.color,
+            metallic: self.metallic,
+            perceptual_roughness: self.roughness,
+            ..default()
+        }
+    }
+}
+
+/// The ball the player picked, used unless the level says otherwise.
+#[derive(Default, Debug, Copy, Clone, Resource)]
+pub struct SelectedBall(pub BallType);
+
+pub fn collect_ball_pickups(
+    mut players: Query<&mut BallType, With<Player>>,
+    mut events: EventReader<PickupEvent>,
+) {
+    for event in events.read() {
+        if let PickupKind::Ball(ball) = event.kind {
+            for mut player_ball in players.iter_mut() {
+                *player_ball = ball;
+            }
+
+            info!("Changed to the {} ball.", ball.name());
+        }
+    }
+}
@@ -0,0 +1,507 @@
+//! The camera that follows the ball around.
+
+use super::config::PlayerConfig;
+use super::{player_exists, Player};
+use crate::actions::{Action, ActionState};
+use crate::replay::is_playing_back;
+use crate::AppState;
+use bevy::core_pipeline::bloom::{BloomCompositeMode, BloomSettings};
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+pub fn setup(app: &mut App) {
+    app.init_resource::<CameraSettings>()
+        .add_systems(Startup, setup_camera)
+        .add_systems(
+            Update,
+            (
+                rotate_camera,
+                recenter_camera
+                    .run_if(player_exists)
+                    .run_if(in_state(AppState::InGame)),
+            )
+                .chain()
+                .run_if(not(is_playing_back)),
+        )
+        .add_systems(Update, zoom_camera.run_if(in_state(AppState::InGame)))
+        .add_systems(
+            PostUpdate,
+            move_camera
+                .run_if(player_exists)
+                .run_if(in_state(AppState::InGame)),
+        );
+}
+
+pub fn setup_visuals(app: &mut App) {
+    app.add_systems(Update, add_camera_visuals);
+}
+
+/// How far the camera turns per pixel of mouse movement at the default sensitivity.
+pub const MOUSE_SPEED: f32 = 0.0025;
+/// How fast the camera turns with the right stick all the way over at the default sensitivity, in
+/// radians per second.
+pub const STICK_SPEED: f32 = 3.0;
+
+pub const MIN_CAMERA_DISTANCE: f32 = 2.0;
+pub const MAX_CAMERA_DISTANCE: f32 = 15.0;
+/// How fast the zoom keys move the camera, in units per second.
+const ZOOM_SPEED: f32 = 8.0;
+/// How far one notch of the scroll wheel moves the camera.
+const SCROLL_ZOOM_STEP: f32 = 0.5;
+/// Scrolling by pixels, like on a touchpad, moves this much per pixel.
+const PIXEL_ZOOM_STEP: f32 = 0.02;
+
+/// How the camera decides where to look from.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CameraMode {
+    /// Turned freely by the player.
+    #[default]
+    Orbit,
+    /// Always swings round behind the direction the ball is rolling.
+    Chase,
+    /// Never turns, so the level is always seen from the same side.
+    FixedAngle,
+}
+
+impl CameraMode {
+    pub const ALL: [CameraMode; 3] = [CameraMode::Orbit, CameraMode::Chase, CameraMode::FixedAngle];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CameraMode::Orbit => "Orbit",
+            CameraMode::Chase => "Chase",
+            CameraMode::FixedAngle => "Fixed",
+        }
+    }
+}
+
+/// How the mouse and right stick turn the camera.
+#[derive(Debug, Copy, Clone, Resource)]
+pub struct CameraSettings {
+    /// Radians turned per pixel of mouse movement.
+    pub mouse_speed: f32,
+    /// Radians turned per second with the stick all the way over.
+    pub stick_speed: f32,
+    pub invert_y: bool,
+    pub mode: CameraMode,
+    /// Whether the orbit camera swings back behind the ball after it's left alone for a while.
+    pub auto_recenter: bool,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        CameraSettings {
+            mouse_speed: MOUSE_SPEED,
+            stick_speed: STICK_SPEED,
+            invert_y: false,
+            mode: CameraMode::Orbit,
+            auto_recenter: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct PlayerCamera {
+    pub pitch: f32,
+    pub yaw: f32,
+    pub distance: f32,
+    /// How far the camera really is from the ball, closer than `distance` while something is in
+    /// the way.
+    pub clear_distance: f32,
+    /// The point the camera looks at, trailing a little behind the ball.
+    pub focus: Vec3,
+    /// How long it's been since the player last turned the camera, in seconds.
+    pub idle_time: f32,
+    /// The camera zone the ball is in.
+    pub zone: Option<Entity>,
+    /// Where the camera was when the ball last went in or out of a zone.
+    pub blend_from: Transform,
+    /// How far the camera has moved from `blend_from` to where it should be, from 0 to 1.
+    pub blend: f32,
+    /// How long the current blend takes, in seconds.
+    pub blend_time: f32,
+}
+
+impl Default for PlayerCamera {
+    fn default() -> Self {
+        PlayerCamera {
+            pitch: PI / 4.0,
+            yaw: 0.0,
+            distance: 5.0,
+            clear_distance: 5.0,
+            focus: Vec3::ZERO,
+            idle_time: 0.0,
+            zone: None,
+            blend_from: Transform::IDENTITY,
+            blend: 1.0,
+            blend_time: 0.0,
+        }
+    }
+}
+
+impl PlayerCamera {
+    pub fn get_looking(&self) -> Vec3 {
+        -Vec3::new(self.yaw.sin(), 0.0, self.yaw.cos())
+    }
+}
+
+/// Where a camera zone puts the camera.
+#[derive(Debug, Clone)]
+pub enum CameraPlacement {
+    /// Orbits the ball as usual.
+    Orbit,
+    /// Stays in one place, turning to follow the ball.
+    Fixed(Vec3),
+    /// Slides along a path of straight lines to the point closest to the ball.
+    Rail(Vec<Vec3>),
+}
+
+/// Changes how the camera behaves while the ball is inside it.
+#[derive(Debug, Clone, Component)]
+pub struct CameraZone {
+    pub placement: CameraPlacement,
+    /// Locks the camera facing this way, in radians.
+    pub yaw: Option<f32>,
+    pub distance: Option<f32>,
+    /// In radians
+    pub min_pitch: Option<f32>,
+    /// In radians
+    pub max_pitch: Option<f32>,
+    /// How long the camera takes to move over when the ball goes in or out, in seconds.
+    pub blend_time: f32,
+    /// Where zones overlap the one with the highest priority is used.
+    pub priority: i32,
+}
+
+pub fn setup_camera(mut commands: Commands) {
+    commands
+        .spawn(PlayerCamera::default())
+        .insert(TransformBundle::from_transform(
+            Transform::from_xyz(0.0, 5.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ));
+}
+
+fn add_camera_visuals(
+    cameras: Query<(Entity, &Transform), Added<PlayerCamera>>,
+    mut commands: Commands,
+) {
+    for (entity, &transform) in cameras.iter() {
+        commands
+            .entity(entity)
+            .insert(Camera3dBundle {
+                transform,
+                camera_3d: Camera3d {
+                    clear_color: ClearColorConfig::Custom(Color::BLACK),
+                    ..default()
+                },
+                camera: Camera {
+                    hdr: true,
+                    ..default()
+                },
+                tonemapping: Tonemapping::TonyMcMapface,
+                ..default()
+            })
+            .insert(BloomSettings {
+                composite_mode: BloomCompositeMode::Additive,
+                ..default()
+            });
+    }
+}
+
+pub fn rotate_camera(
+    mut camera: Query<&mut PlayerCamera>,
+    settings: Res<CameraSettings>,
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    mut mouse: EventReader<MouseMotion>,
+) {
+    if let Some(mut camera) = camera.iter_mut().next() {
+        let invert = if settings.invert_y { -1.0 } else { 1.0 };
+
+        let mut turn: Vec2 = mouse
+            .read()
+            .map(|mouse| -mouse.delta * settings.mouse_speed)
+            .sum();
+        // pushing the stick up looks up, like moving the mouse up
+        turn += Vec2::new(-actions.camera_stick.x, actions.camera_stick.y)
+            * settings.stick_speed
+            * time.delta_seconds();
+
+        match settings.mode {
+            CameraMode::Orbit => {}
+            // the chase camera picks its own yaw
+            CameraMode::Chase => turn.x = 0.0,
+            CameraMode::FixedAngle => turn = Vec2::ZERO,
+        }
+
+        if turn != Vec2::ZERO {
+            camera.yaw += turn.x;
+            camera.pitch =
+                (camera.pitch + turn.y * invert).clamp(-PI / 2.0 + 0.001, PI / 2.0 - 0.001);
+            camera.idle_time = 0.0;
+        } else {
+            camera.idle_time += time.delta_seconds();
+        }
+    } else {
+        mouse.clear();
+    }
+}
+
+/// The speed the ball has to be going before the camera swings round behind it.
+const RECENTER_MIN_SPEED: f32 = 1.0;
+
+/// Swings the camera round behind the direction the ball is rolling.
+pub fn recenter_camera(
+    mut camera: Query<&mut PlayerCamera>,
+    player: Query<&Velocity, With<Player>>,
+    settings: Res<CameraSettings>,
+    config: Res<PlayerConfig>,
+    time: Res<Time>,
+) {
+    let mut camera = camera.single_mut();
+    let velocity = player.single().linvel;
+
+    let recenter = match settings.mode {
+        CameraMode::Orbit => {
+            settings.auto_recenter && camera.idle_time >= config.camera_recenter_delay
+        }
+        CameraMode::Chase => true,
+        CameraMode::FixedAngle => false,
+    };
+
+    let horizontal = Vec2::new(velocity.x, velocity.z);
+    if recenter && horizontal.length() > RECENTER_MIN_SPEED {
+        // the camera looks along -z at a yaw of 0
+        let target = f32::atan2(-horizontal.x, -horizontal.y);
+        let difference = (target - camera.yaw + PI).rem_euclid(2.0 * PI) - PI;
+        let blend = 1.0 - (-config.camera_recenter_speed * time.delta_seconds()).exp();
+        camera.yaw += difference * blend;
+    }
+}
+
+pub fn zoom_camera(
+    mut camera: Query<&mut PlayerCamera>,
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    mut scroll: EventReader<MouseWheel>,
+) {
+    let mut zoom =
+        actions.axis(Action::ZoomOut, Action::ZoomIn) * ZOOM_SPEED * time.delta_seconds();
+    for event in scroll.read() {
+        zoom += match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_ZOOM_STEP,
+            MouseScrollUnit::Pixel => event.y * PIXEL_ZOOM_STEP,
+        };
+    }
+
+    if zoom != 0.0 {
+        for mut camera in camera.iter_mut() {
+            camera.distance =
+                (camera.distance - zoom).clamp(MIN_CAMERA_DISTANCE, MAX_CAMERA_DISTANCE);
+        }
+    }
+}
+
+/// How far the camera stays in front of whatever it is pulled in by.
+const CAMERA_MARGIN: f32 = 0.3;
+/// The closest the camera gets when pulled in, just outside the ball.
+const MIN_CLEAR_DISTANCE: f32 = 0.8;
+/// How quickly the camera moves in when blocked and back out when clear again. Moving in has to be
+/// quick so walls don't show through.
+const CAMERA_PULL_IN_RATE: f32 = 25.0;
+const CAMERA_EASE_OUT_RATE: f32 = 3.0;
+/// How far ahead of the ball the camera looks at most.
+const MAX_LOOK_AHEAD: f32 = 3.0;
+/// The camera jumps straight to the ball when it's further away than this, like after respawning.
+const FOCUS_SNAP_DISTANCE: f32 = 5.0;
+
+pub fn move_camera(
+    mut camera: Query<(&mut Transform, &mut PlayerCamera)>,
+    player: Query<(Entity, &Transform, &Velocity), (With<Player>, Without<PlayerCamera>)>,
+    zones: Query<(Entity, &CameraZone, &CollidingEntities)>,
+    rapier_context: Res<RapierContext>,
+    config: Res<PlayerConfig>,
+    time: Res<Time>,
+) {
+    let (mut transform, mut player_camera) = camera.single_mut();
+    let (player_entity, player, velocity) = player.single();
+
+    let look_ahead = (Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z)
+        * config.camera_look_ahead)
+        .clamp_length_max(MAX_LOOK_AHEAD);
+    let target = player.translation + look_ahead;
+
+    // the ball only moves on physics ticks, following it smoothly hides that
+    if player_camera.focus.distance(target) > FOCUS_SNAP_DISTANCE || config.camera_smoothing <= 0.0
+    {
+        player_camera.focus = target;
+    } else {
+        let blend = 1.0 - (-time.delta_seconds() / config.camera_smoothing).exp();
+        player_camera.focus = player_camera.focus.lerp(target, blend);
+    }
+    let focus = player_camera.focus;
+
+    let zone = zones
+        .iter()
+        .filter(|(_, _, colliding)| colliding.contains(player_entity))
+        .max_by_key(|(_, zone, _)| zone.priority);
+    let zone_entity = zone.map(|(entity, _, _)| entity);
+    let zone = zone.map(|(_, zone, _)| zone);
+
+    if zone_entity != player_camera.zone {
+        // leaving a zone takes as long as going into it did
+        if let Some(zone) = zone {
+            player_camera.blend_time = zone.blend_time;
+        }
+        player_camera.zone = zone_entity;
+        player_camera.blend_from = *transform;
+        player_camera.blend = 0.0;
+    }
+
+    if let Some(zone) = zone {
+        if let Some(yaw) = zone.yaw {
+            player_camera.yaw = yaw;
+        }
+        if let Some(min_pitch) = zone.min_pitch {
+            player_camera.pitch = player_camera.pitch.max(min_pitch);
+        }
+        if let Some(max_pitch) = zone.max_pitch {
+            player_camera.pitch = player_camera.pitch.min(max_pitch);
+        }
+    }
+
+    let target = match zone.map(|zone| &zone.placement) {
+        None | Some(CameraPlacement::Orbit) => {
+            let distance = zone
+                .and_then(|zone| zone.distance)
+                .unwrap_or(player_camera.distance);
+            let clear_distance = update_clear_distance(
+                &mut player_camera,
+                player.translation,
+                focus,
+                distance,
+                player_entity,
+                &rapier_context,
+                &time,
+            );
+            calculate_camera_transform(focus, &player_camera, clear_distance)
+        }
+        Some(CameraPlacement::Fixed(position)) => look_from(&mut player_camera, *position, focus),
+        Some(CameraPlacement::Rail(path)) => look_from(
+            &mut player_camera,
+            closest_point_on_path(path, focus),
+            focus,
+        ),
+    };
+
+    player_camera.blend = if player_camera.blend_time > 0.0 {
+        (player_camera.blend + time.delta_seconds() / player_camera.blend_time).min(1.0)
+    } else {
+        1.0
+    };
+
+    if player_camera.blend < 1.0 {
+        let blend = player_camera.blend * player_camera.blend * (3.0 - 2.0 * player_camera.blend);
+        let from = player_camera.blend_from;
+        *transform = Transform {
+            translation: from.translation.lerp(target.translation, blend),
+            rotation: from.rotation.slerp(target.rotation, blend),
+            ..target
+        };
+    } else {
+        *transform = target;
+    }
+}
+
+/// Pulls the camera in front of anything between it and the ball, returning how far from the
+/// focus it ends up. The ray goes from the ball itself rather than the look-ahead focus, which can
+/// be inside a wall the ball is rolling towards.
+fn update_clear_distance(
+    player_camera: &mut PlayerCamera,
+    ball: Vec3,
+    focus: Vec3,
+    distance: f32,
+    player_entity: Entity,
+    rapier_context: &RapierContext,
+    time: &Time,
+) -> f32 {
+    let wanted = focus + camera_direction(player_camera) * distance;
+    let length = wanted.distance(ball);
+    let direction = (wanted - ball).normalize_or_zero();
+    let filter = QueryFilter::default()
+        .exclude_sensors()
+        .exclude_rigid_body(player_entity);
+    let free_distance = rapier_context
+        .cast_ray(ball, direction, length + CAMERA_MARGIN, true, filter)
+        .map_or(distance, |(_, toi)| {
+            // pull in along the arm from the focus by as much as the ray from the ball was cut short
+            (distance * (toi - CAMERA_MARGIN) / length).max(MIN_CLEAR_DISTANCE)
+        });
+
+    let rate = if free_distance < player_camera.clear_distance {
+        CAMERA_PULL_IN_RATE
+    } else {
+        CAMERA_EASE_OUT_RATE
+    };
+    let blend = 1.0 - (-rate * time.delta_seconds()).exp();
+    player_camera.clear_distance += (free_distance - player_camera.clear_distance) * blend;
+
+    player_camera.clear_distance
+}
+
+/// A camera at `position` looking at the ball. The camera's yaw is turned to match so moving
+/// stays relative to what's on screen.
+fn look_from(player_camera: &mut PlayerCamera, position: Vec3, focus: Vec3) -> Transform {
+    let transform = Transform::from_translation(position).looking_at(focus, Vec3::Y);
+
+    let forward = transform.forward();
+    if Vec2::new(forward.x, forward.z).length() > 0.01 {
+        player_camera.yaw = f32::atan2(-forward.x, -forward.z);
+    }
+
+    transform
+}
+
+/// The point on a path of straight lines closest to `point`.
+fn closest_point_on_path(path: &[Vec3], point: Vec3) -> Vec3 {
+    path.windows(2)
+        .map(|segment| {
+            let (start, end) = (segment[0], segment[1]);
+            let along = end - start;
+            if along.length_squared() <= f32::EPSILON {
+                return start;
+            }
+            let t = ((point - start).dot(along) / along.length_squared()).clamp(0.0, 1.0);
+            start + along * t
+        })
+        .min_by(|a, b| {
+            a.distance_squared(point)
+                .total_cmp(&b.distance_squared(point))
+        })
+        .or(path.first().copied())
+        .unwrap_or(point)
+}
+
+/// The direction from the ball to the camera.
+fn camera_direction(player_camera: &PlayerCamera) -> Vec3 {
+    Vec3::new(
+        player_camera.pitch.cos() * player_camera.yaw.sin(),
+        -player_camera.pitch.sin(),
+        player_camera.pitch.cos() * player_camera.yaw.cos(),
+    )
+}
+
+fn calculate_camera_transform(
+    focus: Vec3,
+    player_camera: &PlayerCamera,
+    distance: f32,
+) -> Transform {
+    let camera_offset = camera_direction(player_camera) * distance;
+
+    Transform::from_translation(focus + camera_offset).looking_at(focus, Vec3::Y)
+}
@@ -0,0 +1,298 @@
+//! The numbers that decide how the ball feels, loaded from a file so they can be tuned while the
+//! game is running.
+
+use crate::level::PlayerSpawnPoint;
+use crate::util::fnv1a;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, BoxedFuture, LoadContext};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+
+pub fn setup(app: &mut App) {
+    app.init_resource::<PlayerConfig>()
+        .init_resource::<PlayerConfigHandle>()
+        .init_asset::<SerialPlayerConfig>()
+        .init_asset_loader::<PlayerConfigLoader>()
+        .add_systems(Update, update_player_config.before(super::add_player));
+}
+
+/// Loads the player config from the assets directory. Without it the built in defaults are used.
+pub struct PlayerConfigPlugin;
+
+impl Plugin for PlayerConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_player_config);
+    }
+}
+
+const PLAYER_CONFIG_PATH: &str = "default.player.kdl";
+
+/// How the ball moves and looks, after the config file and the level have had their say.
+#[derive(Debug, Copy, Clone, PartialEq, Resource)]
+pub struct PlayerConfig {
+    pub radius: f32,
+    /// How hard the ball is spun when moving.
+    pub torque: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    /// How hard the ball is pushed sideways while in the air.
+    pub air_control: f32,
+    /// The horizontal speed where moving stops pushing the ball any faster.
+    pub top_speed: f32,
+    /// How quickly the push fades towards the top speed. 0 pushes fully right up to it, 1 fades
+    /// linearly and higher values fade sooner.
+    pub acceleration_curve: f32,
+    /// How much harder the ball is pushed when moving against the direction it's going.
+    pub brake_strength: f32,
+    /// How hard the ball is slowed for each unit of speed it is over the top speed.
+    pub speed_limit_stiffness: f32,
+    /// How high a jump off flat ground goes.
+    pub jump_height: f32,
+    /// How long after leaving the ground the ball can still jump, in seconds.
+    pub coyote_time: f32,
+    /// How long a jump pressed just before landing is remembered, in seconds.
+    pub jump_buffer_time: f32,
+    /// The steepest ground that can be jumped off, in radians.
+    pub max_slope: f32,
+    pub light_intensity: f32,
+    /// How long the camera takes to catch up with the ball, in seconds. 0 follows it exactly.
+    pub camera_smoothing: f32,
+    /// How far ahead of the ball the camera looks, in seconds of the ball's movement.
+    pub camera_look_ahead: f32,
+    /// How long the camera has to be left alone before it swings back behind the ball, in
+    /// seconds.
+    pub camera_recenter_delay: f32,
+    /// How quickly the camera swings back behind the ball.
+    pub camera_recenter_speed: f32,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        PlayerConfig {
+            radius: 0.5,
+            torque: 1.0,
+            linear_damping: 0.25,
+            angular_damping: 0.25,
+            air_control: 1.5,
+            top_speed: 10.0,
+            acceleration_curve: 1.0,
+            brake_strength: 2.0,
+            speed_limit_stiffness: 1.0,
+            jump_height: 1.2,
+            coyote_time: 0.1,
+            jump_buffer_time: 0.1,
+            max_slope: 50f32.to_radians(),
+            light_intensity: 500.0,
+            camera_smoothing: 0.08,
+            camera_look_ahead: 0.25,
+            camera_recenter_delay: 1.5,
+            camera_recenter_speed: 2.0,
+        }
+    }
+}
+
+impl PlayerConfig {
+    /// This config with every value that is set in `overrides` replaced.
+    pub fn with_overrides(mut self, overrides: &SerialPlayerConfig) -> PlayerConfig {
+        let replace = |value: &mut f32, new: Option<f32>| {
+            if let Some(new) = new {
+                *value = new;
+            }
+        };
+
+        replace(&mut self.radius, overrides.radius);
+        replace(&mut self.torque, overrides.torque);
+        replace(&mut self.linear_damping, overrides.linear_damping);
+        replace(&mut self.angular_damping, overrides.angular_damping);
+        replace(&mut self.air_control, overrides.air_control);
+        replace(&mut self.top_speed, overrides.top_speed);
+        replace(&mut self.acceleration_curve, overrides.acceleration_curve);
+        replace(&mut self.brake_strength, overrides.brake_strength);
+        replace(
+            &mut self.speed_limit_stiffness,
+            overrides.speed_limit_stiffness,
+        );
+        replace(&mut self.jump_height, overrides.jump_height);
+        replace(&mut self.coyote_time, overrides.coyote_time);
+        replace(&mut self.jump_buffer_time, overrides.jump_buffer_time);
+        replace(
+            &mut self.max_slope,
+            overrides.max_slope.map(f32::to_radians),
+        );
+        replace(&mut self.light_intensity, overrides.light_intensity);
+        replace(&mut self.camera_smoothing, overrides.camera_smoothing);
+        replace(&mut self.camera_look_ahead, overrides.camera_look_ahead);
+        replace(
+            &mut self.camera_recenter_delay,
+            overrides.camera_recenter_delay,
+        );
+        replace(
+            &mut self.camera_recenter_speed,
+            overrides.camera_recenter_speed,
+        );
+
+        self
+    }
+
+    /// A hash of every value, used to check that replays are played with the config they were
+    /// recorded with.
+    pub fn content_hash(&self) -> u64 {
+        fnv1a(format!("{:?}", self).as_bytes())
+    }
+
+    /// How much of the push in a direction the ball gets at its current horizontal velocity:
+    /// all of it from standing still, less towards the top speed and extra when braking.
+    pub fn acceleration_scale(&self, velocity: Vec3, direction: Vec3) -> f32 {
+        let speed = velocity.dot(direction.normalize_or_zero());
+
+        if speed < 0.0 {
+            self.brake_strength
+        } else {
+            (1.0 - speed / self.top_speed)
+                .clamp(0.0, 1.0)
+                .powf(self.acceleration_curve)
+        }
+    }
+
+    /// Slows the ball back down when something like a slope takes it over the top speed.
+    pub fn speed_limit_force(&self, velocity: Vec3) -> Vec3 {
+        let speed = velocity.length();
+
+        if speed > self.top_speed {
+            -velocity / speed * (speed - self.top_speed) * self.speed_limit_stiffness
+        } else {
+            Vec3::ZERO
+        }
+    }
+}
+
+/// Player parameters as written in the config file or a level's spawn point. Anything left out
+/// keeps its previous value.
+#[derive(Default, Debug, Copy, Clone, knuffel::Decode, TypeUuid, Asset, TypePath)]
+#[uuid = "3f0d6c1e-8b7a-4c55-9a2e-5d0f6b1c7e42"]
+pub struct SerialPlayerConfig {
+    #[knuffel(child, unwrap(argument))]
+    pub radius: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub torque: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub linear_damping: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub angular_damping: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub air_control: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub top_speed: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub acceleration_curve: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub brake_strength: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub speed_limit_stiffness: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub jump_height: Option<f32>,
+
+    /// In seconds
+    #[knuffel(child, unwrap(argument))]
+    pub coyote_time: Option<f32>,
+
+    /// In seconds
+    #[knuffel(child, unwrap(argument))]
+    pub jump_buffer_time: Option<f32>,
+
+    /// In degrees
+    #[knuffel(child, unwrap(argument))]
+    pub max_slope: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub light_intensity: Option<f32>,
+
+    /// In seconds
+    #[knuffel(child, unwrap(argument))]
+    pub camera_smoothing: Option<f32>,
+
+    /// In seconds
+    #[knuffel(child, unwrap(argument))]
+    pub camera_look_ahead: Option<f32>,
+
+    /// In seconds
+    #[knuffel(child, unwrap(argument))]
+    pub camera_recenter_delay: Option<f32>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub camera_recenter_speed: Option<f32>,
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PlayerConfigLoader;
+
+impl AssetLoader for PlayerConfigLoader {
+    type Asset = SerialPlayerConfig;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut str = String::new();
+            reader.read_to_string(&mut str).await?;
+            match knuffel::parse(&load_context.path().to_string_lossy(), &str) {
+                Ok(res) => Ok(res),
+                Err(err) => {
+                    error!("{:?}", miette::Report::new(err));
+                    anyhow::bail!("Error loading player config")
+                }
+            }
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["player.kdl"]
+    }
+}
+
+/// The config file, kept loaded so changes to it are picked up.
+#[derive(Default, Debug, Clone, Resource)]
+pub struct PlayerConfigHandle {
+    pub handle: Option<Handle<SerialPlayerConfig>>,
+}
+
+fn load_player_config(mut config: ResMut<PlayerConfigHandle>, assets: Res<AssetServer>) {
+    config.handle = Some(assets.load(PLAYER_CONFIG_PATH));
+}
+
+/// Combines the defaults, the config file and the current level's overrides.
+pub fn update_player_config(
+    handle: Res<PlayerConfigHandle>,
+    assets: Res<Assets<SerialPlayerConfig>>,
+    spawn_points: Query<&PlayerSpawnPoint>,
+    mut config: ResMut<PlayerConfig>,
+) {
+    let mut new_config = PlayerConfig::default();
+
+    if let Some(file) = handle.handle.as_ref().and_then(|handle| assets.get(handle)) {
+        new_config = new_config.with_overrides(file);
+    }
+    if let Some(spawn_point) = spawn_points.iter().next() {
+        new_config = new_config.with_overrides(&spawn_point.player);
+    }
+
+    if *config != new_config {
+        *config = new_config;
+
+        info!("Player config changed.");
+    }
+}
@@ -0,0 +1,408 @@
+pub mod abilities;
+pub mod ball;
+pub mod camera;
+pub mod config;
+
+use crate::actions::{Action, ActionState};
+use crate::level::{LevelLoadedEvent, LevelRemovedEvent, PlayerSpawnPoint};
+use crate::replay::is_playing_back;
+use crate::AppState;
+use abilities::Abilities;
+use ball::{BallType, SelectedBall};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use camera::PlayerCamera;
+use config::PlayerConfig;
+
+/// Everything about the player except how it looks, so it can run without a renderer.
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInput>()
+            .add_systems(Update, (add_player, remove_player))
+            // picking up a ball changes its physics from the next tick on
+            .add_systems(
+                FixedUpdate,
+                apply_ball_physics.after(ball::collect_ball_pickups),
+            )
+            .add_systems(Update, read_input.run_if(not(is_playing_back)))
+            .add_systems(
+                FixedUpdate,
+                (update_grounded, move_player, jump_player)
+                    .chain()
+                    .run_if(player_exists)
+                    .run_if(in_state(AppState::InGame))
+                    .before(PhysicsSet::SyncBackend),
+            );
+        abilities::setup(app);
+        ball::setup(app);
+        camera::setup(app);
+        config::setup(app);
+    }
+}
+
+/// The meshes, lights and camera settings that make the player visible.
+pub struct PlayerVisualsPlugin;
+
+impl Plugin for PlayerVisualsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (add_player_visuals, update_player_visuals));
+        camera::setup_visuals(app);
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct Player;
+
+/// The player's health. The player dies when it runs out.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Health {
+        Health { current: max, max }
+    }
+}
+
+/// Time left before the player can be hit by a hazard again.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct Invulnerability {
+    pub remaining: f32,
+}
+
+/// What the player wants the ball to do, read from the keyboard or fed in by a replay.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Resource)]
+pub struct PlayerInput {
+    /// Movement relative to the camera, with x to the right and y forward.
+    pub movement: Vec2,
+    pub jump: bool,
+    pub dash: bool,
+    pub ground_pound: bool,
+    pub brake: bool,
+}
+
+impl PlayerInput {
+    /// Whether the player is trying to do anything at all.
+    pub fn is_active(&self) -> bool {
+        self.movement != Vec2::ZERO || self.jump || self.dash || self.ground_pound || self.brake
+    }
+}
+
+/// What the ball is standing on, updated every physics tick from its contacts.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Grounded {
+    /// The normal of the flattest ground being touched, pointing away from it.
+    pub normal: Option<Vec3>,
+    /// The normal of the ground that was touched last, for jumping during coyote time.
+    pub last_normal: Vec3,
+    /// How long ago the ball was last on the ground, in seconds.
+    pub time_in_air: f32,
+}
+
+impl Default for Grounded {
+    fn default() -> Self {
+        Grounded {
+            normal: None,
+            last_normal: Vec3::Y,
+            time_in_air: f32::INFINITY,
+        }
+    }
+}
+
+impl Grounded {
+    pub fn is_grounded(&self) -> bool {
+        self.normal.is_some()
+    }
+
+    /// How steep the ground is, in radians.
+    pub fn slope_angle(&self) -> Option<f32> {
+        self.normal.map(|normal| normal.angle_between(Vec3::Y))
+    }
+}
+
+/// Remembers jump presses so one pressed slightly too early still happens.
+#[derive(Default, Debug, Copy, Clone, Component)]
+pub struct JumpBuffer {
+    /// How long ago jump was pressed, until the press is used up or forgotten.
+    pressed_for: Option<f32>,
+    was_held: bool,
+}
+
+pub fn no_player_exists(player: Query<(), With<Player>>) -> bool {
+    player.is_empty()
+}
+
+pub fn player_exists(player: Query<(), With<Player>>) -> bool {
+    !no_player_exists(player)
+}
+
+pub fn add_player(
+    player: Query<(), With<Player>>,
+    mut level_load: EventReader<LevelLoadedEvent>,
+    spawnpoint: Query<(&Transform, &PlayerSpawnPoint)>,
+    config: Res<PlayerConfig>,
+    selected_ball: Res<SelectedBall>,
+    mut commands: Commands,
+) {
+    if player.is_empty() {
+        if let Some(level) = level_load.read().next() {
+            let default_spawn = PlayerSpawnPoint::default();
+            let (spawnpoint, spawn) = match spawnpoint.get(level.entities.spawn) {
+                Ok((trans, spawn)) => (trans.translation, spawn),
+                Err(_) => (Vec3::new(0.0, 0.5, 0.0), &default_spawn),
+            };
+            let ball = spawn.ball.unwrap_or(selected_ball.0);
+
+            let player_transform = Transform::from_translation(spawnpoint);
+            commands
+                .spawn(Player::default())
+                .insert(Health::new(spawn.max_health))
+                .insert(Invulnerability::default())
+                .insert(ball)
+                .insert(ball_physics(&config, ball))
+                .insert(RigidBody::Dynamic)
+                .insert(ExternalForce::default())
+                .insert(Velocity::default())
+                .insert(Grounded::default())
+                .insert(JumpBuffer::default())
+                .insert(Abilities::new(&spawn.abilities))
+                .insert(Sleeping::disabled())
+                .insert(TransformBundle::from_transform(player_transform.clone()))
+                .insert(VisibilityBundle::default())
+                .insert(ActiveEvents::CONTACT_FORCE_EVENTS | ActiveEvents::COLLISION_EVENTS);
+
+            info!("Player spawned as the {} ball.", ball.name());
+        }
+    }
+
+    level_load.clear();
+}
+
+/// The parts of the player's physics that depend on the type of ball.
+fn ball_physics(config: &PlayerConfig, ball: BallType) -> impl Bundle {
+    let properties = ball.properties();
+
+    (
+        Collider::ball(properties.radius(config)),
+        ColliderMassProperties::Density(properties.density),
+        Friction::coefficient(properties.friction),
+        Restitution::coefficient(properties.restitution),
+        properties.damping(config),
+    )
+}
+
+/// Applies config changes and ball changes to a ball that is already rolling.
+fn apply_ball_physics(
+    config: Res<PlayerConfig>,
+    players: Query<(Entity, Ref<BallType>), With<Player>>,
+    mut commands: Commands,
+) {
+    for (entity, ball) in players.iter() {
+        if config.is_changed() || (ball.is_changed() && !ball.is_added()) {
+            commands.entity(entity).insert(ball_physics(&config, *ball));
+        }
+    }
+}
+
+fn add_player_visuals(
+    players: Query<(Entity, &BallType), Added<Player>>,
+    config: Res<PlayerConfig>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, &ball) in players.iter() {
+        let properties = ball.properties();
+
+        commands
+            .entity(entity)
+            .insert(player_mesh(&config, ball, &mut meshes))
+            .insert(materials.add(properties.material()))
+            .with_children(|builder| {
+                builder.spawn(PointLightBundle {
+                    point_light: PointLight {
+                        intensity: config.light_intensity,
+                        shadows_enabled: true,
+                        color: properties.light_color,
+                        ..default()
+                    },
+                    ..default()
+                });
+            });
+    }
+}
+
+fn player_mesh(config: &PlayerConfig, ball: BallType, meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+    meshes.add(
+        shape::UVSphere {
+            radius: ball.properties().radius(config),
+            sectors: 32,
+            stacks: 32,
+        }
+        .into(),
+    )
+}
+
+/// Keeps the ball's look in step with the config as it is tuned and with its type as it changes.
+fn update_player_visuals(
+    config: Res<PlayerConfig>,
+    mut players: Query<
+        (
+            Ref<BallType>,
+            &mut Handle<Mesh>,
+            &mut Handle<StandardMaterial>,
+            &Children,
+        ),
+        With<Player>,
+    >,
+    mut lights: Query<&mut PointLight>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (ball, mut mesh, mut material, children) in players.iter_mut() {
+        if config.is_changed() || ball.is_changed() {
+            let properties = ball.properties();
+            *mesh = player_mesh(&config, *ball, &mut meshes);
+            *material = materials.add(properties.material());
+
+            for &child in children.iter() {
+                if let Ok(mut light) = lights.get_mut(child) {
+                    light.intensity = config.light_intensity;
+                    light.color = properties.light_color;
+                }
+            }
+        }
+    }
+}
+
+pub fn remove_player(
+    mut level_remove: EventReader<LevelRemovedEvent>,
+    players: Query<Entity, With<Player>>,
+    mut commands: Commands,
+) {
+    if level_remove.read().next().is_some() {
+        for player in players.iter() {
+            commands.entity(player).despawn_recursive();
+
+            info!("Player removed.");
+        }
+    }
+
+    level_remove.clear();
+}
+
+pub fn read_input(mut input: ResMut<PlayerInput>, actions: Res<ActionState>) {
+    let new_input = PlayerInput {
+        movement: actions.movement(),
+        jump: actions.pressed(Action::Jump),
+        dash: actions.pressed(Action::Dash),
+        ground_pound: actions.pressed(Action::GroundPound),
+        brake: actions.pressed(Action::Brake),
+    };
+
+    if *input != new_input {
+        *input = new_input;
+    }
+}
+
+pub fn move_player(
+    mut player: Query<(&mut ExternalForce, &Velocity, &Grounded, &BallType), With<Player>>,
+    camera: Query<&PlayerCamera>,
+    input: Res<PlayerInput>,
+    config: Res<PlayerConfig>,
+) {
+    let camera = camera.single();
+    let (mut force, velocity, grounded, ball) = player.single_mut();
+    let properties = ball.properties();
+    // the config is tuned for the normal ball, heavier balls need more force for the same effect
+    let mass_scale = properties.mass(&config) / BallType::Normal.properties().mass(&config);
+
+    // sticks push the ball more gently when they aren't all the way over
+    let movement = (camera.get_looking() * input.movement.y
+        + Vec3::cross(camera.get_looking(), Vec3::Y) * input.movement.x)
+        .clamp_length_max(1.0);
+    let horizontal = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z);
+    let push = movement * config.acceleration_scale(horizontal, movement);
+
+    force.torque = Vec3::cross(Vec3::Y, push) * config.torque * properties.torque_scale;
+    force.force = config.speed_limit_force(horizontal) * mass_scale;
+    match grounded.normal {
+        Some(normal) => force.force -= normal * properties.stickiness * properties.mass(&config),
+        None => force.force += push * config.air_control * properties.torque_scale,
+    }
+}
+
+/// Contacts further apart than this don't count as touching the ground.
+const GROUND_CONTACT_DISTANCE: f32 = 0.05;
+
+pub fn update_grounded(
+    mut player: Query<(Entity, &mut Grounded), With<Player>>,
+    rapier_context: Res<RapierContext>,
+    config: Res<PlayerConfig>,
+    time: Res<Time>,
+) {
+    let (player_entity, mut grounded) = player.single_mut();
+
+    let mut normal: Option<Vec3> = None;
+    for pair in rapier_context.contacts_with(player_entity) {
+        for manifold in pair.manifolds() {
+            // manifold normals point from the first collider to the second
+            let ground_normal = if pair.collider1() == player_entity {
+                -manifold.normal()
+            } else {
+                manifold.normal()
+            };
+
+            let touching = manifold
+                .points()
+                .any(|point| point.dist() <= GROUND_CONTACT_DISTANCE);
+            let walkable = ground_normal.angle_between(Vec3::Y) <= config.max_slope;
+
+            if touching && walkable && normal.map_or(true, |normal| ground_normal.y > normal.y) {
+                normal = Some(ground_normal);
+            }
+        }
+    }
+
+    grounded.normal = normal;
+    if let Some(normal) = normal {
+        grounded.last_normal = normal;
+        grounded.time_in_air = 0.0;
+    } else {
+        grounded.time_in_air += time.delta_seconds();
+    }
+}
+
+pub fn jump_player(
+    mut player: Query<(&mut Velocity, &mut Grounded, &mut JumpBuffer), With<Player>>,
+    input: Res<PlayerInput>,
+    config: Res<PlayerConfig>,
+    rapier_config: Res<RapierConfiguration>,
+    time: Res<Time>,
+) {
+    let (mut velocity, mut grounded, mut buffer) = player.single_mut();
+
+    if input.jump && !buffer.was_held {
+        buffer.pressed_for = Some(0.0);
+    } else if let Some(pressed_for) = buffer.pressed_for {
+        let pressed_for = pressed_for + time.delta_seconds();
+        buffer.pressed_for = (pressed_for <= config.jump_buffer_time).then_some(pressed_for);
+    }
+    buffer.was_held = input.jump;
+
+    if buffer.pressed_for.is_some() && grounded.time_in_air <= config.coyote_time {
+        let normal = grounded.last_normal;
+        let jump_speed = (2.0 * rapier_config.gravity.length() * config.jump_height).sqrt();
+
+        // the jump tops up the speed away from the ground rather than adding to it
+        velocity.linvel += normal * (jump_speed - velocity.linvel.dot(normal)).max(0.0);
+
+        buffer.pressed_for = None;
+        // no second jump from the same ground while still in coyote time
+        grounded.time_in_air = f32::INFINITY;
+    }
+}
@@ -0,0 +1,292 @@
+use crate::actions::{Action, ActionState};
+use crate::level::logic::goal::LevelCompletedEvent;
+use crate::level::serial::SerialLevel;
+use crate::level::{LevelLoadedEvent, LevelState};
+use crate::player::ball::{BallType, SelectedBall};
+use crate::player::camera::PlayerCamera;
+use crate::player::config::{update_player_config, PlayerConfig};
+use crate::player::{add_player, jump_player, move_player, PlayerInput};
+use crate::timer::start_timer;
+use crate::util::data_dir;
+use crate::AppState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ReplayState::Recording(Replay::default()))
+            .add_systems(Startup, start_playback_from_args)
+            .add_systems(
+                Update,
+                (
+                    reset_replay.after(update_player_config).before(add_player),
+                    save_completed_replay,
+                    save_replay_on_key,
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (record_tick, play_tick)
+                    .before(move_player)
+                    .before(jump_player)
+                    .before(start_timer)
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// Bumped whenever the replay format or anything affecting simulation changes.
+pub const REPLAY_VERSION: u32 = 7;
+
+/// The player's input during a single physics tick.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TickInput {
+    pub x: f32,
+    pub y: f32,
+    pub jump: bool,
+    pub dash: bool,
+    pub ground_pound: bool,
+    pub brake: bool,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// A recording of every tick of input during an attempt at a level.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    /// Asset path of the level the replay was recorded on.
+    pub level: String,
+    /// [`SerialLevel::content_hash`] of the level the replay was recorded on.
+    pub level_hash: u64,
+    /// The ball the player picked, which the level may have overridden.
+    pub ball: BallType,
+    /// [`PlayerConfig::content_hash`] of the config in effect, with the level's overrides.
+    pub config_hash: u64,
+    pub inputs: Vec<TickInput>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Replay> {
+        let replay: Replay = ron::from_str(&fs::read_to_string(path)?)?;
+
+        if replay.version != REPLAY_VERSION {
+            anyhow::bail!(
+                "Replay version {} is not supported (expected {})",
+                replay.version,
+                REPLAY_VERSION
+            );
+        }
+
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, ron::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    /// Saves the replay to the replays directory under a name made from the level and the time.
+    pub fn save_new(&self) -> anyhow::Result<PathBuf> {
+        let Some(dir) = data_dir() else {
+            anyhow::bail!("No data directory available");
+        };
+
+        let level = Path::new(&self.level)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split('.').next())
+            .unwrap_or("level");
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+
+        let path = dir.join("replays").join(format!("{}-{}.ron", level, time));
+        self.save(&path)?;
+
+        Ok(path)
+    }
+}
+
+#[derive(Debug, Clone, Resource)]
+pub enum ReplayState {
+    /// Recording the player's input for the current attempt.
+    Recording(Replay),
+    /// Feeding a recorded replay into the game instead of live input.
+    Playing { replay: Replay, tick: usize },
+    /// A replay has finished playing and live input has taken over again, until the next level
+    /// load starts recording.
+    Finished,
+}
+
+impl ReplayState {
+    /// Whether the current attempt is the player's own, rather than a replay or the end of one.
+    /// Only these count towards personal bests and progress.
+    pub fn is_recording(&self) -> bool {
+        matches!(self, ReplayState::Recording(_))
+    }
+}
+
+pub fn is_playing_back(state: Res<ReplayState>) -> bool {
+    matches!(*state, ReplayState::Playing { .. })
+}
+
+/// Starts playing the replay given by `--replay <file>` on the command line.
+fn start_playback_from_args(
+    mut state: ResMut<ReplayState>,
+    mut level_state: ResMut<LevelState>,
+    mut app_state: ResMut<NextState<AppState>>,
+    assets: Res<AssetServer>,
+) {
+    let mut args = std::env::args().skip_while(|arg| arg != "--replay").skip(1);
+    let Some(path) = args.next() else {
+        return;
+    };
+
+    match Replay::load(&path) {
+        Ok(replay) => {
+            info!("Playing replay {} of {}.", path, replay.level);
+
+            level_state.handle = Some(assets.load(replay.level.clone()));
+            app_state.set(AppState::Loading);
+            *state = ReplayState::Playing { replay, tick: 0 };
+        }
+        Err(err) => error!("Error loading replay {}: {}", path, err),
+    }
+}
+
+/// Restarts the recording or the playback whenever the level is (re)loaded.
+fn reset_replay(
+    mut state: ResMut<ReplayState>,
+    mut selected_ball: ResMut<SelectedBall>,
+    config: Res<PlayerConfig>,
+    level_state: Res<LevelState>,
+    levels: Res<Assets<SerialLevel>>,
+    mut level_load: EventReader<LevelLoadedEvent>,
+) {
+    if level_load.read().next().is_some() {
+        let handle = level_state.handle.as_ref();
+        let level = handle
+            .and_then(|handle| handle.path())
+            .map(|path| path.to_string())
+            .unwrap_or_default();
+        let level_hash = handle
+            .and_then(|handle| levels.get(handle))
+            .map_or(0, |level| level.content_hash());
+        let config_hash = config.content_hash();
+
+        if let ReplayState::Playing { replay, tick } = &mut *state {
+            if replay.level_hash != level_hash {
+                warn!("Replay was recorded on a different version of {}.", level);
+            }
+            if replay.config_hash != config_hash {
+                warn!(
+                    "Replay was recorded with a different player config and may not play back \
+                     the same."
+                );
+            }
+
+            // stays picked until the profile's settings are applied again
+            selected_ball.0 = replay.ball;
+            *tick = 0;
+        } else {
+            // a finished replay goes back to recording the player's own attempts
+            *state = ReplayState::Recording(Replay {
+                version: REPLAY_VERSION,
+                level,
+                level_hash,
+                ball: selected_ball.0,
+                config_hash,
+                inputs: vec![],
+            });
+        }
+    }
+
+    level_load.clear();
+}
+
+fn record_tick(
+    mut state: ResMut<ReplayState>,
+    input: Res<PlayerInput>,
+    camera: Query<&PlayerCamera>,
+) {
+    if let ReplayState::Recording(replay) = &mut *state {
+        let camera = camera.single();
+
+        replay.inputs.push(TickInput {
+            x: input.movement.x,
+            y: input.movement.y,
+            jump: input.jump,
+            dash: input.dash,
+            ground_pound: input.ground_pound,
+            brake: input.brake,
+            yaw: camera.yaw,
+            pitch: camera.pitch,
+        });
+    }
+}
+
+fn play_tick(
+    mut state: ResMut<ReplayState>,
+    mut input: ResMut<PlayerInput>,
+    mut camera: Query<&mut PlayerCamera>,
+) {
+    if let ReplayState::Playing { replay, tick } = &mut *state {
+        if let Some(tick_input) = replay.inputs.get(*tick) {
+            *input = PlayerInput {
+                movement: Vec2::new(tick_input.x, tick_input.y),
+                jump: tick_input.jump,
+                dash: tick_input.dash,
+                ground_pound: tick_input.ground_pound,
+                brake: tick_input.brake,
+            };
+
+            let mut camera = camera.single_mut();
+            camera.yaw = tick_input.yaw;
+            camera.pitch = tick_input.pitch;
+
+            *tick += 1;
+        } else {
+            info!("Replay finished.");
+
+            *input = PlayerInput::default();
+            *state = ReplayState::Finished;
+        }
+    }
+}
+
+fn save_completed_replay(
+    state: Res<ReplayState>,
+    mut completed_events: EventReader<LevelCompletedEvent>,
+) {
+    if completed_events.read().next().is_some() {
+        save_recording(&state);
+    }
+
+    completed_events.clear();
+}
+
+/// Lets the player save their current attempt at any time, like when reporting a bug.
+fn save_replay_on_key(state: Res<ReplayState>, actions: Res<ActionState>) {
+    if actions.just_pressed(Action::SaveReplay) {
+        save_recording(&state);
+    }
+}
+
+fn save_recording(state: &ReplayState) {
+    if let ReplayState::Recording(replay) = state {
+        match replay.save_new() {
+            Ok(path) => info!("Replay saved to {:?}.", path),
+            Err(err) => error!("Error saving replay: {}", err),
+        }
+    }
+}
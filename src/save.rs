@@ -0,0 +1,625 @@
+use crate::actions::Bindings;
+use crate::ghost::GhostTrack;
+use crate::level::logic::goal::LevelCompletedEvent;
+use crate::level::logic::pickup::{PickupEvent, PickupKind};
+use crate::level::serial::SerialLevel;
+use crate::level::{LevelLoadedEvent, LevelState};
+use crate::player::ball::BallType;
+use crate::player::camera::CameraMode;
+use crate::replay::ReplayState;
+use crate::timer::BestRun;
+use crate::util::{data_dir, with_added_extension, write_atomic};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Profile::load_last_used())
+            .insert_resource(ProfileList::scan())
+            .add_event::<ProfileEvent>()
+            .init_resource::<RunCollectibles>()
+            .add_systems(Update, handle_profile_events)
+            .add_systems(
+                Update,
+                (reset_run_collectibles, find_collectibles, record_progress).chain(),
+            );
+    }
+}
+
+/// Bumped whenever the profile format changes in a way old profiles need migrating for.
+pub const SAVE_VERSION: u32 = 1;
+
+/// The level every new profile starts with.
+pub const FIRST_LEVEL: &str = "levels/level0.level.kdl";
+
+/// Options the player has chosen, applied by [`SettingsPlugin`](crate::settings::SettingsPlugin).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Whether to race against a ghost of the best run.
+    pub ghost: bool,
+    /// How fast the mouse turns the camera, as a multiple of the default speed.
+    pub mouse_sensitivity: f32,
+    pub invert_y: bool,
+    /// How fast the right stick turns the camera, as a multiple of the default speed.
+    pub stick_sensitivity: f32,
+    /// How far a stick has to move before it counts, as a fraction of its range.
+    pub stick_deadzone: f32,
+    /// How far the camera stays from the ball.
+    pub camera_distance: f32,
+    pub camera_mode: CameraMode,
+    /// Whether the camera swings back behind the ball when it's left alone.
+    pub auto_recenter: bool,
+    /// Vertical field of view in degrees.
+    pub fov: f32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub bloom: bool,
+    pub shadows: ShadowQuality,
+    /// Volume of all sound, from 0 to 1.
+    pub volume: f32,
+    pub bindings: Bindings,
+    /// The ball to play as in levels that don't choose one.
+    pub ball: BallType,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            ghost: true,
+            mouse_sensitivity: 1.0,
+            invert_y: false,
+            stick_sensitivity: 1.0,
+            stick_deadzone: 0.15,
+            camera_distance: 5.0,
+            camera_mode: CameraMode::Orbit,
+            auto_recenter: true,
+            fov: 45.0,
+            fullscreen: false,
+            vsync: true,
+            bloom: true,
+            shadows: ShadowQuality::Medium,
+            volume: 1.0,
+            bindings: default(),
+            ball: BallType::Normal,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShadowQuality {
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+/// Everything about one player's progress that is kept between runs of the game.
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// The profile's file name, without the extension.
+    #[serde(skip)]
+    pub id: String,
+    pub version: u32,
+    pub name: String,
+    /// Asset paths of the levels the player can start from the main menu, in the order they were
+    /// unlocked.
+    pub unlocked_levels: Vec<String>,
+    /// Asset paths of the levels the player has finished at least once.
+    pub completed_levels: BTreeSet<String>,
+    /// The best run of each level, keyed by level asset path.
+    pub best_runs: BTreeMap<String, BestRun>,
+    /// The ids of the collectibles found in each level, keyed by level asset path.
+    pub collectibles: BTreeMap<String, BTreeSet<String>>,
+    pub settings: Settings,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile {
+            id: String::new(),
+            version: SAVE_VERSION,
+            name: "Player".to_string(),
+            unlocked_levels: vec![FIRST_LEVEL.to_string()],
+            completed_levels: default(),
+            best_runs: default(),
+            collectibles: default(),
+            settings: default(),
+        }
+    }
+}
+
+/// Where profiles are kept.
+fn profiles_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("profiles"))
+}
+
+fn profile_path(id: &str) -> Option<PathBuf> {
+    profiles_dir().map(|dir| dir.join(format!("{}.ron", id)))
+}
+
+/// Remembers which profile was used last.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct ProfileIndex {
+    last_used: Option<String>,
+}
+
+impl ProfileIndex {
+    fn path() -> Option<PathBuf> {
+        profiles_dir().map(|dir| dir.join("index.ron"))
+    }
+
+    fn load() -> ProfileIndex {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|str| ron::from_str(&str).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = Self::path() else {
+            anyhow::bail!("No data directory available");
+        };
+
+        write_atomic(&path, ron::to_string(self)?.as_bytes())
+    }
+}
+
+impl Profile {
+    /// Loads the profile that was used last, creating one if there are none.
+    pub fn load_last_used() -> Profile {
+        if let Err(err) = move_single_profile() {
+            error!("Error moving profile: {}", err);
+        }
+
+        let list = ProfileList::scan();
+        let id = ProfileIndex::load()
+            .last_used
+            .filter(|id| list.profiles.iter().any(|profile| &profile.id == id))
+            .or_else(|| list.profiles.first().map(|profile| profile.id.clone()));
+
+        let mut profile = match id {
+            Some(id) => Profile::load(&id),
+            None => {
+                info!("No profile found, starting a new one.");
+                Profile::create("Player 1")
+            }
+        };
+        if let Err(err) = import_old_records(&mut profile) {
+            error!("Error importing old records: {}", err);
+        }
+        profile.remember();
+
+        profile
+    }
+
+    /// Loads a profile, falling back to its backup and then to a fresh profile if it is
+    /// unreadable.
+    pub fn load(id: &str) -> Profile {
+        let fresh = Profile {
+            id: id.to_string(),
+            ..default()
+        };
+
+        let Some(path) = profile_path(id) else {
+            warn!("No data directory available, progress will not be saved.");
+            return fresh;
+        };
+
+        let mut profile = match Self::read(&path) {
+            Ok(Some(profile)) => profile,
+            Ok(None) => fresh,
+            Err(err) => {
+                error!("Error loading profile {:?}: {}", path, err);
+
+                // keep the broken file around instead of overwriting it on the next save
+                let time = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |time| time.as_secs());
+                let corrupt = with_added_extension(&path, &format!("corrupt-{}", time));
+                if let Err(err) = fs::rename(&path, &corrupt) {
+                    error!("Error moving {:?} out of the way: {}", path, err);
+                }
+
+                let backup = with_added_extension(&path, "bak");
+                match Self::read(&backup) {
+                    Ok(Some(profile)) => {
+                        warn!("Recovered profile from {:?}.", backup);
+                        profile
+                    }
+                    _ => {
+                        warn!("Starting a new profile.");
+                        fresh
+                    }
+                }
+            }
+        };
+        profile.id = id.to_string();
+
+        profile
+    }
+
+    /// Makes and saves a new, empty profile.
+    pub fn create(name: &str) -> Profile {
+        let profile = Profile {
+            id: ProfileList::scan().next_id(),
+            name: name.to_string(),
+            ..default()
+        };
+
+        if let Err(err) = profile.save() {
+            error!("Error saving profile: {}", err);
+        }
+
+        profile
+    }
+
+    /// Reads a profile, giving `None` if there isn't one.
+    fn read(path: &Path) -> anyhow::Result<Option<Profile>> {
+        let str = match fs::read_to_string(path) {
+            Ok(str) => str,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut profile: Profile = ron::from_str(&str)?;
+        if profile.version > SAVE_VERSION {
+            anyhow::bail!(
+                "Profile version {} is newer than this game supports ({})",
+                profile.version,
+                SAVE_VERSION
+            );
+        }
+
+        // no migrations yet, missing fields are filled in with their defaults
+        profile.version = SAVE_VERSION;
+
+        Ok(Some(profile))
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = profile_path(&self.id) else {
+            anyhow::bail!("No data directory available");
+        };
+
+        write_atomic(
+            &path,
+            ron::ser::to_string_pretty(self, default())?.as_bytes(),
+        )
+    }
+
+    /// Makes this the profile that is loaded the next time the game starts.
+    fn remember(&self) {
+        let index = ProfileIndex {
+            last_used: Some(self.id.clone()),
+        };
+        if let Err(err) = index.save() {
+            error!("Error saving profile index: {}", err);
+        }
+    }
+
+    pub fn unlock(&mut self, level: &str) {
+        if !self
+            .unlocked_levels
+            .iter()
+            .any(|unlocked| unlocked == level)
+        {
+            info!("Unlocked {}.", level);
+
+            self.unlocked_levels.push(level.to_string());
+        }
+    }
+}
+
+/// Moves the profile and ghosts from before there could be more than one profile to where the
+/// first profile keeps them.
+fn move_single_profile() -> anyhow::Result<()> {
+    let Some(dir) = data_dir() else {
+        return Ok(());
+    };
+    let Some((new, ghosts)) = profile_path("profile-1").zip(GhostTrack::profile_dir("profile-1"))
+    else {
+        return Ok(());
+    };
+
+    let old = dir.join("profile.ron");
+    if !old.exists() || new.exists() {
+        return Ok(());
+    }
+
+    info!("Moving {:?} to {:?}.", old, new);
+
+    fs::create_dir_all(new.parent().unwrap_or(&dir))?;
+    fs::rename(&old, &new)?;
+
+    let old_ghosts = dir.join("ghosts");
+    if old_ghosts.exists() {
+        fs::create_dir_all(&ghosts)?;
+        for entry in fs::read_dir(old_ghosts)? {
+            let path = entry?.path();
+            if let Some(name) = path.file_name().filter(|_| path.is_file()) {
+                fs::rename(&path, ghosts.join(name))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Personal bests as they were kept before profiles, in `records.ron`.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct OldRecords {
+    levels: BTreeMap<String, BestRun>,
+}
+
+/// Moves the personal bests from before there were profiles into the profile loaded after
+/// updating, which is the first one created if there were none yet. The old file is renamed
+/// rather than deleted so it is only imported once.
+fn import_old_records(profile: &mut Profile) -> anyhow::Result<()> {
+    let Some(dir) = data_dir() else {
+        return Ok(());
+    };
+
+    let old = dir.join("records.ron");
+    let str = match fs::read_to_string(&old) {
+        Ok(str) => str,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let records: OldRecords = ron::from_str(&str)?;
+
+    info!("Importing personal bests from {:?}.", old);
+
+    for (level, run) in records.levels {
+        let better = profile
+            .best_runs
+            .get(&level)
+            .map_or(true, |best| run.ticks < best.ticks);
+        if better {
+            profile.best_runs.insert(level, run);
+        }
+    }
+
+    profile.save()?;
+    fs::rename(&old, with_added_extension(&old, "imported"))?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct ProfileSummary {
+    pub id: String,
+    pub name: String,
+}
+
+/// Every profile on this machine, for the profile select screen.
+#[derive(Default, Debug, Clone, Resource)]
+pub struct ProfileList {
+    pub profiles: Vec<ProfileSummary>,
+}
+
+impl ProfileList {
+    pub fn scan() -> ProfileList {
+        let Some(entries) = profiles_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+            return ProfileList::default();
+        };
+
+        let mut profiles: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let id = path
+                    .file_name()?
+                    .to_str()?
+                    .strip_suffix(".ron")?
+                    .to_string();
+                if id == "index" {
+                    return None;
+                }
+
+                let name = Profile::read(&path)
+                    .ok()
+                    .flatten()
+                    .map_or_else(|| id.clone(), |profile| profile.name);
+
+                Some(ProfileSummary { id, name })
+            })
+            .collect();
+        profiles.sort_by_key(|profile| profile_number(&profile.id));
+
+        ProfileList { profiles }
+    }
+
+    /// An id no profile is using yet.
+    fn next_id(&self) -> String {
+        let next = self
+            .profiles
+            .iter()
+            .map(|profile| profile_number(&profile.id))
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        format!("profile-{}", next)
+    }
+}
+
+fn profile_number(id: &str) -> u32 {
+    id.strip_prefix("profile-")
+        .and_then(|number| number.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Sent by the profile select screen to change profiles.
+#[derive(Debug, Clone, Event)]
+pub enum ProfileEvent {
+    Select { id: String },
+    Create,
+    Rename { id: String, name: String },
+    Copy { id: String },
+    Delete { id: String },
+}
+
+fn handle_profile_events(
+    mut profile: ResMut<Profile>,
+    mut list: ResMut<ProfileList>,
+    mut events: EventReader<ProfileEvent>,
+) {
+    for event in events.read() {
+        match event {
+            ProfileEvent::Select { id } => {
+                info!("Switching to profile {}.", id);
+
+                *profile = Profile::load(id);
+                profile.remember();
+            }
+            ProfileEvent::Create => {
+                let name = format!("Player {}", list.profiles.len() + 1);
+                *profile = Profile::create(&name);
+                profile.remember();
+            }
+            ProfileEvent::Rename { id, name } => {
+                let mut renamed = if *id == profile.id {
+                    profile.clone()
+                } else {
+                    Profile::load(id)
+                };
+                renamed.name = name.clone();
+
+                if let Err(err) = renamed.save() {
+                    error!("Error saving profile: {}", err);
+                }
+                if *id == profile.id {
+                    *profile = renamed;
+                }
+            }
+            ProfileEvent::Copy { id } => {
+                let mut copy = Profile::load(id);
+                copy.id = list.next_id();
+                copy.name = format!("{} (copy)", copy.name);
+
+                if let Err(err) = copy.save() {
+                    error!("Error saving profile: {}", err);
+                }
+                if let Err(err) = copy_ghosts(id, &copy.id) {
+                    error!("Error copying ghosts: {}", err);
+                }
+            }
+            ProfileEvent::Delete { id } => {
+                info!("Deleting profile {}.", id);
+
+                if let Some(path) = profile_path(id) {
+                    for path in [with_added_extension(&path, "bak"), path] {
+                        if let Err(err) = fs::remove_file(&path) {
+                            if err.kind() != ErrorKind::NotFound {
+                                error!("Error deleting {:?}: {}", path, err);
+                            }
+                        }
+                    }
+                }
+                if let Some(ghosts) = GhostTrack::profile_dir(id).filter(|dir| dir.exists()) {
+                    if let Err(err) = fs::remove_dir_all(&ghosts) {
+                        error!("Error deleting {:?}: {}", ghosts, err);
+                    }
+                }
+
+                // there always has to be a profile to play on
+                if *id == profile.id {
+                    *profile = Profile::load_last_used();
+                }
+            }
+        }
+
+        *list = ProfileList::scan();
+    }
+}
+
+fn copy_ghosts(from: &str, to: &str) -> anyhow::Result<()> {
+    let Some((from, to)) = GhostTrack::profile_dir(from).zip(GhostTrack::profile_dir(to)) else {
+        return Ok(());
+    };
+    if !from.exists() {
+        return Ok(());
+    }
+
+    fs::create_dir_all(&to)?;
+    for entry in fs::read_dir(from)? {
+        let path = entry?.path();
+        if let Some(name) = path.file_name() {
+            fs::copy(&path, to.join(name))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Collectibles found during the current attempt, which only count once the level is finished.
+#[derive(Default, Debug, Clone, Resource)]
+pub struct RunCollectibles {
+    pub found: Vec<String>,
+}
+
+fn reset_run_collectibles(
+    mut run: ResMut<RunCollectibles>,
+    mut level_load: EventReader<LevelLoadedEvent>,
+) {
+    if level_load.read().next().is_some() {
+        run.found.clear();
+    }
+
+    level_load.clear();
+}
+
+fn find_collectibles(mut run: ResMut<RunCollectibles>, mut events: EventReader<PickupEvent>) {
+    for event in events.read() {
+        if let PickupKind::Collectible { id } = &event.kind {
+            run.found.push(id.clone());
+        }
+    }
+}
+
+/// Saves the completed level, its collectibles and the level it unlocks.
+///
+/// The best run has already been updated by
+/// [`record_splits`](crate::timer::record_splits) during the tick the goal was reached.
+fn record_progress(
+    mut profile: ResMut<Profile>,
+    mut run: ResMut<RunCollectibles>,
+    level_state: Res<LevelState>,
+    levels: Res<Assets<SerialLevel>>,
+    replay: Res<ReplayState>,
+    mut completed_events: EventReader<LevelCompletedEvent>,
+) {
+    if completed_events.read().next().is_some() {
+        if !replay.is_recording() {
+            info!("Not saving progress from a replay.");
+        } else if let Some(handle) = &level_state.handle {
+            if let Some(path) = handle.path().map(|path| path.to_string()) {
+                profile.completed_levels.insert(path.clone());
+
+                let found = std::mem::take(&mut run.found);
+                profile.collectibles.entry(path).or_default().extend(found);
+
+                if let Some(next) = levels.get(handle).and_then(|level| level.next_level()) {
+                    profile.unlock(next);
+                }
+
+                if let Err(err) = profile.save() {
+                    error!("Error saving profile: {}", err);
+                }
+            }
+        }
+    }
+
+    completed_events.clear();
+}
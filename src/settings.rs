@@ -0,0 +1,160 @@
+use crate::actions::{Bindings, StickSettings};
+use crate::player::ball::SelectedBall;
+use crate::player::camera::{CameraSettings, PlayerCamera, MOUSE_SPEED, STICK_SPEED};
+use crate::save::{Profile, ShadowQuality};
+use bevy::core_pipeline::bloom::{BloomCompositeMode, BloomSettings};
+use bevy::prelude::*;
+use bevy::window::{PresentMode, WindowMode};
+
+/// Applies the current profile's [`Settings`](crate::save::Settings) as soon as they change.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                apply_window_settings,
+                apply_camera_settings,
+                apply_shadow_settings,
+                apply_audio_settings,
+                apply_bindings,
+                apply_ball,
+            ),
+        );
+    }
+}
+
+fn apply_window_settings(profile: Res<Profile>, mut windows: Query<&mut Window>) {
+    if profile.is_changed() {
+        let settings = &profile.settings;
+        let mode = if settings.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        let present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+
+        for mut window in windows.iter_mut() {
+            if window.mode != mode {
+                window.mode = mode;
+            }
+            if window.present_mode != present_mode {
+                window.present_mode = present_mode;
+            }
+        }
+    }
+}
+
+fn apply_camera_settings(
+    profile: Res<Profile>,
+    mut camera_settings: ResMut<CameraSettings>,
+    mut cameras: Query<(
+        Entity,
+        &mut PlayerCamera,
+        &mut Projection,
+        Has<BloomSettings>,
+    )>,
+    added: Query<(), Added<Projection>>,
+    mut commands: Commands,
+    mut applied_distance: Local<Option<f32>>,
+) {
+    if profile.is_changed() || !added.is_empty() {
+        let settings = &profile.settings;
+
+        // the profile changes for all sorts of reasons, which mustn't undo zooming in and out
+        let set_distance = !added.is_empty() || *applied_distance != Some(settings.camera_distance);
+        *applied_distance = Some(settings.camera_distance);
+
+        *camera_settings = CameraSettings {
+            mouse_speed: MOUSE_SPEED * settings.mouse_sensitivity,
+            stick_speed: STICK_SPEED * settings.stick_sensitivity,
+            invert_y: settings.invert_y,
+            mode: settings.camera_mode,
+            auto_recenter: settings.auto_recenter,
+        };
+
+        for (entity, mut camera, mut projection, has_bloom) in cameras.iter_mut() {
+            if set_distance {
+                camera.distance = settings.camera_distance;
+            }
+
+            if let Projection::Perspective(perspective) = projection.as_mut() {
+                perspective.fov = settings.fov.to_radians();
+            }
+
+            if settings.bloom && !has_bloom {
+                commands.entity(entity).insert(BloomSettings {
+                    composite_mode: BloomCompositeMode::Additive,
+                    ..default()
+                });
+            } else if !settings.bloom && has_bloom {
+                commands.entity(entity).remove::<BloomSettings>();
+            }
+        }
+    }
+}
+
+/// Lights are spawned with shadows, this turns them off again if the player doesn't want them.
+fn apply_shadow_settings(
+    profile: Res<Profile>,
+    mut shadow_map: ResMut<PointLightShadowMap>,
+    mut lights: Query<&mut PointLight>,
+) {
+    let quality = profile.settings.shadows;
+
+    if profile.is_changed() {
+        shadow_map.size = match quality {
+            ShadowQuality::Off | ShadowQuality::Low => 512,
+            ShadowQuality::Medium => 1024,
+            ShadowQuality::High => 2048,
+        };
+    }
+
+    for mut light in lights.iter_mut() {
+        if profile.is_changed() || light.is_added() {
+            let enabled = quality != ShadowQuality::Off;
+            if light.shadows_enabled != enabled {
+                light.shadows_enabled = enabled;
+            }
+        }
+    }
+}
+
+fn apply_audio_settings(
+    profile: Res<Profile>,
+    mut global_volume: ResMut<GlobalVolume>,
+    sinks: Query<&AudioSink>,
+) {
+    if profile.is_changed() {
+        *global_volume = GlobalVolume::new(profile.settings.volume);
+
+        // the global volume only applies to sounds started after it changes
+        for sink in sinks.iter() {
+            sink.set_volume(profile.settings.volume);
+        }
+    }
+}
+
+fn apply_bindings(
+    profile: Res<Profile>,
+    mut bindings: ResMut<Bindings>,
+    mut stick_settings: ResMut<StickSettings>,
+) {
+    if profile.is_changed() {
+        if *bindings != profile.settings.bindings {
+            *bindings = profile.settings.bindings.clone();
+        }
+        stick_settings.deadzone = profile.settings.stick_deadzone;
+    }
+}
+
+fn apply_ball(profile: Res<Profile>, mut selected_ball: ResMut<SelectedBall>) {
+    if profile.is_changed() {
+        selected_ball.0 = profile.settings.ball;
+    }
+}
@@ -0,0 +1,145 @@
+use super::{hold, Harness};
+use crate::level::logic::DeathCause;
+use crate::player::config::PlayerConfig;
+use crate::replay::TickInput;
+use bevy::prelude::*;
+
+const LEVEL: &str = "levels/level0.level.kdl";
+
+#[test]
+fn player_rolls_forward() {
+    let mut harness = Harness::new();
+    harness.load_level(LEVEL);
+    harness.step(30);
+    let start = harness.player_position();
+
+    harness.play(hold(
+        TickInput {
+            y: 1.0,
+            ..default()
+        },
+        90,
+    ));
+    harness.step(90);
+
+    let end = harness.player_position();
+    assert!(end.z < start.z - 1.0, "{:?} -> {:?}", start, end);
+    assert!(harness.deaths.is_empty());
+}
+
+#[test]
+fn falling_off_the_level_kills_the_player() {
+    let mut harness = Harness::new();
+    harness.load_level(LEVEL);
+    harness.step(30);
+    let spawn = harness.player_position();
+
+    harness.teleport(Vec3::new(20.0, 1.0, 20.0));
+    harness.step(180);
+
+    assert_eq!(harness.deaths.len(), 1);
+    assert_eq!(harness.deaths[0].cause, DeathCause::DeathObject);
+    assert!(harness.player_position().distance(spawn) < 1.0);
+}
+
+#[test]
+fn dying_after_a_checkpoint_respawns_there() {
+    let mut harness = Harness::new();
+    harness.load_level(LEVEL);
+    harness.step(30);
+
+    harness.teleport(Vec3::new(0.0, 0.5, -6.0));
+    harness.step(10);
+    harness.teleport(Vec3::new(20.0, 1.0, 20.0));
+    harness.step(180);
+
+    assert_eq!(harness.deaths.len(), 1);
+    // dropped onto the floor at the checkpoint, not pushed out of it
+    let position = harness.player_position();
+    let radius = PlayerConfig::default().radius;
+    assert!(Vec2::new(position.x, position.z).distance(Vec2::new(0.0, -6.0)) < 0.05);
+    assert!((position.y - radius).abs() < 0.05);
+    assert!(harness.player_grounded().is_grounded());
+}
+
+#[test]
+fn reaching_the_goal_completes_the_level() {
+    let mut harness = Harness::new();
+    harness.load_level(LEVEL);
+    harness.step(30);
+    assert_eq!(harness.completions, 0);
+
+    harness.teleport(Vec3::new(0.0, 2.5, -17.5));
+    harness.step(10);
+
+    assert_eq!(harness.completions, 1);
+}
+
+#[test]
+fn jumping_leaves_the_ground() {
+    let mut harness = Harness::new();
+    harness.load_level(LEVEL);
+    harness.step(30);
+    assert!(harness.player_grounded().is_grounded());
+    let start = harness.player_position();
+
+    harness.play(hold(
+        TickInput {
+            jump: true,
+            ..default()
+        },
+        5,
+    ));
+    harness.step(15);
+
+    assert!(!harness.player_grounded().is_grounded());
+    assert!(harness.player_position().y > start.y + 0.5);
+}
+
+#[test]
+fn jumping_on_the_ramp_goes_along_its_normal() {
+    let mut harness = Harness::new();
+    harness.load_level(LEVEL);
+    harness.step(30);
+
+    // the 45 degree ramp up to the goal
+    let ramp = Vec3::new(0.0, 1.0, -14.0);
+    let normal = Quat::from_rotation_x(45f32.to_radians()) * Vec3::Y;
+    harness.teleport(ramp + normal * 0.55);
+    harness.step(3);
+
+    let grounded = harness.player_grounded();
+    assert!(grounded.is_grounded());
+    let slope = grounded.slope_angle().unwrap().to_degrees();
+    assert!((slope - 45.0).abs() < 2.0, "{}", slope);
+
+    harness.play(hold(
+        TickInput {
+            jump: true,
+            ..default()
+        },
+        1,
+    ));
+    harness.step(10);
+
+    let height = (harness.player_position() - ramp).dot(normal);
+    assert!(height > 1.0, "{}", height);
+}
+
+#[test]
+fn resetting_does_not_give_lives_back() {
+    let mut harness = Harness::new();
+    harness.load_level("levels/level1.level.kdl");
+    harness.step(30);
+    assert_eq!(harness.lives(), Some(3));
+
+    harness.teleport(Vec3::new(20.0, 1.0, 20.0));
+    harness.step(180);
+    assert_eq!(harness.deaths.len(), 1);
+    assert_eq!(harness.lives(), Some(2));
+
+    harness.restart();
+    harness.step(30);
+
+    assert_eq!(harness.lives(), Some(2));
+}
@@ -0,0 +1,90 @@
+use crate::menu::profile::{setup_renaming, RenameProfileButton, RenamingProfile};
+use crate::save::{ProfileEvent, ProfileList, ProfileSummary};
+use crate::AppState;
+use bevy::ecs::event::ManualEventReader;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::{ButtonState, InputPlugin};
+use bevy::prelude::*;
+
+fn press_return(app: &mut App, state: ButtonState) {
+    app.world.send_event(KeyboardInput {
+        scan_code: 0,
+        key_code: Some(KeyCode::Return),
+        state,
+        window: Entity::PLACEHOLDER,
+    });
+}
+
+#[test]
+fn enter_on_the_rename_button_starts_renaming() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, InputPlugin))
+        .add_state::<AppState>()
+        .add_event::<ReceivedCharacter>()
+        .add_event::<ProfileEvent>()
+        .insert_resource(ProfileList {
+            profiles: vec![ProfileSummary {
+                id: "profile-1".to_string(),
+                name: "Player 1".to_string(),
+            }],
+        });
+    setup_renaming(&mut app);
+
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::ProfileMenu);
+    app.update();
+
+    let button = app
+        .world
+        .spawn((
+            Interaction::None,
+            RenameProfileButton {
+                id: "profile-1".to_string(),
+            },
+        ))
+        .id();
+    app.update();
+
+    // enter presses the focused button in the same frame the key goes down
+    press_return(&mut app, ButtonState::Pressed);
+    *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+    app.update();
+
+    let mut events = ManualEventReader::<ProfileEvent>::default();
+    assert_eq!(
+        app.world.resource::<RenamingProfile>().id.as_deref(),
+        Some("profile-1")
+    );
+    assert_eq!(
+        events
+            .read(app.world.resource::<Events<ProfileEvent>>())
+            .count(),
+        0
+    );
+
+    *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Hovered;
+    press_return(&mut app, ButtonState::Released);
+    app.world.send_event(ReceivedCharacter {
+        window: Entity::PLACEHOLDER,
+        char: '!',
+    });
+    app.update();
+
+    press_return(&mut app, ButtonState::Pressed);
+    app.update();
+
+    let renamed: Vec<_> = events
+        .read(app.world.resource::<Events<ProfileEvent>>())
+        .cloned()
+        .collect();
+    assert!(
+        matches!(
+            renamed.as_slice(),
+            [ProfileEvent::Rename { id, name }] if id == "profile-1" && name == "Player 1!"
+        ),
+        "{:?}",
+        renamed
+    );
+    assert!(app.world.resource::<RenamingProfile>().id.is_none());
+}
@@ -0,0 +1,227 @@
+//! Runs the game without a window or renderer so gameplay can be tested.
+
+mod gameplay;
+mod menu;
+mod movement;
+mod physics;
+
+use crate::actions::ActionsPlugin;
+use crate::level::logic::goal::LevelCompletedEvent;
+use crate::level::logic::PlayerDiedEvent;
+use crate::level::{LevelLoadedEvent, LevelState, LevelsPlugin};
+use crate::lives::{Lives, LivesPlugin};
+use crate::physics::PhysicsPlugin;
+use crate::player::abilities::{Abilities, AbilityKind};
+use crate::player::config::{PlayerConfigHandle, SerialPlayerConfig};
+use crate::player::{Grounded, Player, PlayerPlugin};
+use crate::replay::{Replay, ReplayPlugin, ReplayState, TickInput};
+use crate::{reset_level, AppState};
+use bevy::ecs::event::ManualEventReader;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::{ButtonState, InputPlugin};
+use bevy::prelude::*;
+use bevy::scene::ScenePlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::Velocity;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a level to load before failing the test.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A headless copy of the game that advances a fixed number of physics ticks per update.
+pub struct Harness {
+    pub app: App,
+    ticks_per_update: usize,
+    died_reader: ManualEventReader<PlayerDiedEvent>,
+    completed_reader: ManualEventReader<LevelCompletedEvent>,
+    /// Every death since the level was loaded.
+    pub deaths: Vec<PlayerDiedEvent>,
+    /// The number of times the goal has been reached since the level was loaded.
+    pub completions: usize,
+}
+
+impl Harness {
+    pub fn new() -> Harness {
+        Self::with_ticks_per_update(1)
+    }
+
+    /// Runs several physics ticks in each update, like a slow machine would.
+    pub fn with_ticks_per_update(ticks_per_update: usize) -> Harness {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            ScenePlugin,
+            TransformPlugin,
+            HierarchyPlugin,
+            InputPlugin,
+        ))
+        // rapier looks for meshes to build colliders from even when nothing is drawn
+        .init_asset::<Mesh>()
+        .add_state::<AppState>()
+        .add_plugins(ActionsPlugin)
+        .add_plugins(PhysicsPlugin {
+            deterministic: true,
+        })
+        .add_plugins(LevelsPlugin)
+        .add_plugins(PlayerPlugin)
+        .add_plugins(ReplayPlugin)
+        .add_plugins(LivesPlugin)
+        .add_systems(Update, reset_level);
+
+        // the same number of ticks every update, no matter how fast the test machine is
+        let tick = app.world.resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            tick * ticks_per_update as u32,
+        ));
+
+        Harness {
+            app,
+            ticks_per_update,
+            died_reader: default(),
+            completed_reader: default(),
+            deaths: vec![],
+            completions: 0,
+        }
+    }
+
+    /// Loads a level from the assets directory and starts playing it on the next step.
+    pub fn load_level(&mut self, path: &str) {
+        let handle = self.app.world.resource::<AssetServer>().load(path);
+        self.app.world.resource_mut::<LevelState>().handle = Some(handle);
+
+        self.wait_for_level(path);
+    }
+
+    /// Presses the reset button and waits for the level to be rebuilt.
+    pub fn restart(&mut self) {
+        self.send_key(KeyCode::R, ButtonState::Pressed);
+        self.app.update();
+        self.send_key(KeyCode::R, ButtonState::Released);
+
+        self.wait_for_level("the restarted level");
+    }
+
+    fn send_key(&mut self, key: KeyCode, state: ButtonState) {
+        self.app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    /// Updates until a level has loaded, then starts playing it.
+    fn wait_for_level(&mut self, name: &str) {
+        let mut loaded = ManualEventReader::<LevelLoadedEvent>::default();
+        let start = Instant::now();
+        loop {
+            self.app.update();
+
+            let events = self.app.world.resource::<Events<LevelLoadedEvent>>();
+            if loaded.read(events).next().is_some() {
+                break;
+            }
+
+            assert!(start.elapsed() < LOAD_TIMEOUT, "Timed out loading {}", name);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        self.app
+            .world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::InGame);
+
+        self.deaths.clear();
+        self.completions = 0;
+    }
+
+    /// Feeds the player this input, one entry per tick, instead of reading the keyboard.
+    pub fn play(&mut self, inputs: impl IntoIterator<Item = TickInput>) {
+        *self.app.world.resource_mut::<ReplayState>() = ReplayState::Playing {
+            replay: Replay {
+                inputs: inputs.into_iter().collect(),
+                ..default()
+            },
+            tick: 0,
+        };
+    }
+
+    /// Runs the game for a number of physics ticks, collecting deaths and completions. The number
+    /// of ticks has to be a multiple of the ticks per update.
+    pub fn step(&mut self, ticks: usize) {
+        assert_eq!(
+            ticks % self.ticks_per_update,
+            0,
+            "Can't step {} ticks {} at a time",
+            ticks,
+            self.ticks_per_update
+        );
+
+        for _ in 0..ticks / self.ticks_per_update {
+            self.app.update();
+
+            let died = self.app.world.resource::<Events<PlayerDiedEvent>>();
+            self.deaths.extend(self.died_reader.read(died).copied());
+
+            let completed = self.app.world.resource::<Events<LevelCompletedEvent>>();
+            self.completions += self.completed_reader.read(completed).count();
+        }
+    }
+
+    /// Tunes the player the way the config file would.
+    pub fn set_player_config(&mut self, config: SerialPlayerConfig) {
+        let handle = self
+            .app
+            .world
+            .resource_mut::<Assets<SerialPlayerConfig>>()
+            .add(config);
+        self.app.world.resource_mut::<PlayerConfigHandle>().handle = Some(handle);
+    }
+
+    /// Gives the player these abilities, as if the level had unlocked them.
+    pub fn set_abilities(&mut self, abilities: &[AbilityKind]) {
+        let mut player = self
+            .app
+            .world
+            .query_filtered::<&mut Abilities, With<Player>>();
+        *player.single_mut(&mut self.app.world) = Abilities::new(abilities);
+    }
+
+    pub fn player_transform(&mut self) -> Transform {
+        let mut player = self.app.world.query_filtered::<&Transform, With<Player>>();
+        *player.single(&self.app.world)
+    }
+
+    pub fn lives(&self) -> Option<u32> {
+        self.app.world.resource::<Lives>().remaining
+    }
+
+    pub fn player_position(&mut self) -> Vec3 {
+        self.player_transform().translation
+    }
+
+    pub fn player_velocity(&mut self) -> Vec3 {
+        let mut player = self.app.world.query_filtered::<&Velocity, With<Player>>();
+        player.single(&self.app.world).linvel
+    }
+
+    pub fn player_grounded(&mut self) -> Grounded {
+        let mut player = self.app.world.query_filtered::<&Grounded, With<Player>>();
+        *player.single(&self.app.world)
+    }
+
+    /// Moves the player somewhere else in the level, keeping their velocity.
+    pub fn teleport(&mut self, pos: Vec3) {
+        let mut player = self
+            .app
+            .world
+            .query_filtered::<&mut Transform, With<Player>>();
+        player.single_mut(&mut self.app.world).translation = pos;
+    }
+}
+
+/// The same input held for a number of ticks.
+pub fn hold(input: TickInput, ticks: usize) -> impl Iterator<Item = TickInput> {
+    std::iter::repeat(input).take(ticks)
+}
@@ -0,0 +1,117 @@
+use super::{hold, Harness};
+use crate::player::abilities::AbilityKind;
+use crate::player::config::SerialPlayerConfig;
+use crate::replay::TickInput;
+use bevy::prelude::*;
+
+const LEVEL: &str = "levels/level0.level.kdl";
+
+fn horizontal_speed(velocity: Vec3) -> f32 {
+    Vec2::new(velocity.x, velocity.z).length()
+}
+
+/// Rolls forward for a while, then holds `input` and returns the speed afterwards.
+fn roll_then(input: TickInput) -> f32 {
+    let mut harness = Harness::new();
+    harness.load_level(LEVEL);
+    harness.step(30);
+
+    harness.play(
+        hold(
+            TickInput {
+                y: 1.0,
+                ..default()
+            },
+            60,
+        )
+        .chain(hold(input, 30)),
+    );
+    harness.step(90);
+
+    horizontal_speed(harness.player_velocity())
+}
+
+#[test]
+fn speed_is_capped() {
+    let mut harness = Harness::new();
+    harness.set_player_config(SerialPlayerConfig {
+        top_speed: Some(1.0),
+        ..default()
+    });
+    harness.load_level(LEVEL);
+    harness.step(30);
+
+    harness.play(hold(
+        TickInput {
+            y: 1.0,
+            ..default()
+        },
+        90,
+    ));
+    harness.step(90);
+
+    let speed = horizontal_speed(harness.player_velocity());
+    assert!(speed < 1.2, "{}", speed);
+}
+
+#[test]
+fn braking_stops_faster_than_rolling_out() {
+    let coasting = roll_then(default());
+    let braking = roll_then(TickInput {
+        y: -1.0,
+        ..default()
+    });
+
+    assert!(braking < coasting, "{} >= {}", braking, coasting);
+}
+
+#[test]
+fn air_control_moves_the_ball_while_falling() {
+    let mut harness = Harness::new();
+    harness.load_level(LEVEL);
+    harness.step(30);
+
+    harness.teleport(Vec3::new(0.0, 8.0, 0.0));
+    harness.play(hold(
+        TickInput {
+            x: 1.0,
+            ..default()
+        },
+        30,
+    ));
+    harness.step(30);
+
+    assert!(!harness.player_grounded().is_grounded());
+    assert!(harness.player_position().x > 0.2);
+}
+
+#[test]
+fn double_jump_goes_higher() {
+    let peak = |abilities: &[AbilityKind]| {
+        let mut harness = Harness::new();
+        harness.load_level(LEVEL);
+        harness.step(30);
+        harness.set_abilities(abilities);
+
+        let jump = TickInput {
+            jump: true,
+            ..default()
+        };
+        harness.play(
+            hold(jump, 5)
+                .chain(hold(default(), 15))
+                .chain(hold(jump, 5)),
+        );
+
+        let mut peak = f32::MIN;
+        for _ in 0..60 {
+            harness.step(1);
+            peak = peak.max(harness.player_position().y);
+        }
+        peak
+    };
+
+    let single = peak(&[]);
+    let double = peak(&[AbilityKind::DoubleJump]);
+    assert!(double > single + 0.5, "{} <= {}", double, single);
+}
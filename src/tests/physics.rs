@@ -0,0 +1,110 @@
+use super::{hold, Harness};
+use crate::level::logic::key::Inventory;
+use crate::level::logic::pickup::Pickup;
+use crate::replay::TickInput;
+use bevy::prelude::*;
+
+/// Plays the same input through a fresh copy of the game and returns where the player ends up.
+fn simulate() -> Transform {
+    let mut harness = Harness::new();
+    harness.load_level("levels/level0.level.kdl");
+    harness.play(
+        hold(
+            TickInput {
+                x: 0.3,
+                y: 1.0,
+                jump: true,
+                ..default()
+            },
+            200,
+        )
+        .chain(hold(
+            TickInput {
+                x: -1.0,
+                yaw: 0.5,
+                ..default()
+            },
+            200,
+        )),
+    );
+    harness.step(450);
+
+    harness.player_transform()
+}
+
+fn to_bits(transform: &Transform) -> [u32; 7] {
+    let [x, y, z] = transform.translation.to_array();
+    let [i, j, k, w] = transform.rotation.to_array();
+    [x, y, z, i, j, k, w].map(f32::to_bits)
+}
+
+#[test]
+fn same_input_gives_identical_results() {
+    let first = simulate();
+    let second = simulate();
+
+    assert_eq!(to_bits(&first), to_bits(&second));
+}
+
+/// Where the player ends up and what happened to them on the way.
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    transform: [u32; 7],
+    deaths: usize,
+    keys: Vec<String>,
+    pickups_left: usize,
+}
+
+/// Rolls past the key in the second level and off the edge, running a number of ticks per update.
+fn simulate_at(ticks_per_update: usize) -> Outcome {
+    let mut harness = Harness::with_ticks_per_update(ticks_per_update);
+    harness.load_level("levels/level1.level.kdl");
+    harness.play(
+        hold(
+            TickInput {
+                x: -0.7,
+                y: 0.7,
+                ..default()
+            },
+            240,
+        )
+        .chain(hold(
+            TickInput {
+                x: -1.0,
+                ..default()
+            },
+            120,
+        )),
+    );
+    harness.step(420);
+
+    let keys = harness
+        .app
+        .world
+        .resource::<Inventory>()
+        .keys
+        .iter()
+        .map(|key| key.id.clone())
+        .collect();
+    let pickups_left = harness
+        .app
+        .world
+        .query::<&Pickup>()
+        .iter(&harness.app.world)
+        .count();
+
+    Outcome {
+        transform: to_bits(&harness.player_transform()),
+        deaths: harness.deaths.len(),
+        keys,
+        pickups_left,
+    }
+}
+
+#[test]
+fn frame_rate_does_not_change_results() {
+    let one = simulate_at(1);
+    let three = simulate_at(3);
+
+    assert_eq!(one, three);
+}
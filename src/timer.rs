@@ -0,0 +1,166 @@
+use crate::level::logic::goal::{
+    reach_checkpoints, reach_goal, CheckpointReachedEvent, LevelCompletedEvent,
+};
+use crate::level::{LevelLoadedEvent, LevelState};
+use crate::player::PlayerInput;
+use crate::replay::ReplayState;
+use crate::save::Profile;
+use crate::{AppState, PHYSICS_TICK_RATE};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct TimerPlugin;
+
+impl Plugin for TimerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpeedrunTimer>()
+            .add_systems(Update, reset_timer)
+            .add_systems(
+                FixedUpdate,
+                // splits are taken after the physics step that reached them but before the tick is
+                // counted, so they are the same at any frame rate
+                (
+                    start_timer,
+                    record_splits.after(reach_checkpoints).after(reach_goal),
+                    tick_timer,
+                )
+                    .chain()
+                    .after(PhysicsSet::Writeback)
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TimerState {
+    /// The level has loaded but the player hasn't moved yet.
+    #[default]
+    Waiting,
+    Running,
+    Finished,
+}
+
+/// Times the current attempt at a level in physics ticks.
+#[derive(Default, Debug, Clone, Resource)]
+pub struct SpeedrunTimer {
+    pub state: TimerState,
+    pub ticks: u64,
+    /// The tick count at each checkpoint reached so far.
+    pub splits: Vec<u64>,
+    /// The asset path of the level being timed.
+    pub level: Option<String>,
+    /// How far ahead (negative) or behind (positive) the personal best the last split was.
+    pub delta: Option<i64>,
+    /// Whether the finished run beat the personal best.
+    pub new_best: bool,
+}
+
+/// The best recorded run of a level.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BestRun {
+    pub ticks: u64,
+    pub splits: Vec<u64>,
+}
+
+/// Converts a number of physics ticks into seconds.
+pub fn ticks_to_seconds(ticks: u64) -> f64 {
+    ticks as f64 / PHYSICS_TICK_RATE
+}
+
+/// Formats a tick count as `m:ss.cc`.
+pub fn format_ticks(ticks: u64) -> String {
+    let seconds = ticks_to_seconds(ticks);
+    let minutes = (seconds / 60.0).floor();
+    format!("{}:{:05.2}", minutes, seconds - minutes * 60.0)
+}
+
+/// Formats a difference in ticks as `+s.cc` or `-s.cc`.
+pub fn format_delta(delta: i64) -> String {
+    let sign = if delta < 0 { '-' } else { '+' };
+    format!("{}{:.2}", sign, ticks_to_seconds(delta.unsigned_abs()))
+}
+
+fn reset_timer(
+    mut timer: ResMut<SpeedrunTimer>,
+    level_state: Res<LevelState>,
+    mut level_load: EventReader<LevelLoadedEvent>,
+) {
+    if level_load.read().next().is_some() {
+        *timer = SpeedrunTimer {
+            level: level_state
+                .handle
+                .as_ref()
+                .and_then(|handle| handle.path())
+                .map(|path| path.to_string()),
+            ..default()
+        };
+    }
+
+    level_load.clear();
+}
+
+/// Starts the timer the first time the player tries to move.
+pub fn start_timer(mut timer: ResMut<SpeedrunTimer>, input: Res<PlayerInput>) {
+    if timer.state == TimerState::Waiting && input.is_active() {
+        timer.state = TimerState::Running;
+    }
+}
+
+pub fn tick_timer(mut timer: ResMut<SpeedrunTimer>) {
+    if timer.state == TimerState::Running {
+        timer.ticks += 1;
+    }
+}
+
+pub fn record_splits(
+    mut timer: ResMut<SpeedrunTimer>,
+    mut profile: ResMut<Profile>,
+    replay: Res<ReplayState>,
+    mut checkpoint_events: EventReader<CheckpointReachedEvent>,
+    mut completed_events: EventReader<LevelCompletedEvent>,
+) {
+    for _ in checkpoint_events.read() {
+        if timer.state == TimerState::Running {
+            let ticks = timer.ticks;
+            let index = timer.splits.len();
+            timer.splits.push(ticks);
+
+            let delta = timer
+                .level
+                .as_ref()
+                .and_then(|level| profile.best_runs.get(level))
+                .and_then(|best| best.splits.get(index))
+                .map(|&best| ticks as i64 - best as i64);
+            timer.delta = delta;
+        }
+    }
+
+    if completed_events.read().next().is_some() {
+        if timer.state == TimerState::Running {
+            timer.state = TimerState::Finished;
+
+            if let Some(level) = timer.level.clone() {
+                let ticks = timer.ticks;
+                let best = profile.best_runs.get(&level).map(|best| best.ticks);
+                timer.delta = best.map(|best| ticks as i64 - best as i64);
+
+                // replays don't count as the player's own runs
+                if replay.is_recording() && best.map_or(true, |best| ticks < best) {
+                    info!("New personal best: {}", format_ticks(ticks));
+
+                    timer.new_best = true;
+                    profile.best_runs.insert(
+                        level,
+                        BestRun {
+                            ticks,
+                            splits: timer.splits.clone(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    completed_events.clear();
+}
@@ -1,3 +1,9 @@
+use directories::ProjectDirs;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
 pub trait ResultExt<R, E> {
     fn report(self) -> R;
 }
@@ -10,3 +16,47 @@ impl<R, E: miette::Diagnostic + Sync + Send + 'static> ResultExt<R, E> for Resul
         }
     }
 }
+
+/// 64-bit FNV-1a hash, used where a hash has to stay the same across builds and platforms.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// The platform-specific directory persistent game data is stored in.
+pub fn data_dir() -> Option<PathBuf> {
+    ProjectDirs::from("com", "Kneelawk", "ball-thing").map(|dirs| dirs.data_dir().to_path_buf())
+}
+
+/// Adds an extra extension to a path, turning `profile.ron` into `profile.ron.bak`.
+pub fn with_added_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut str = OsString::from(path.as_os_str());
+    str.push(".");
+    str.push(extension);
+    PathBuf::from(str)
+}
+
+/// Replaces a file so that a crash part way through leaves either the old or the new contents,
+/// never a mix of the two. The old contents are kept next to it with a `.bak` extension.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp = with_added_extension(path, "tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    if path.exists() {
+        fs::copy(path, with_added_extension(path, "bak"))?;
+    }
+    fs::rename(&tmp, path)?;
+
+    Ok(())
+}